@@ -0,0 +1,107 @@
+use crate::{parser::Arg, Nadawca};
+use serenity::{
+    framework::standard::{Args, Delimiter},
+    model::channel::Message,
+};
+use serde::Deserialize;
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+use tracing::Level;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::{
+    filter::{EnvFilter, Targets},
+    fmt::{self, writer::BoxMakeWriter},
+    prelude::*,
+};
+
+/* dziennik działania bota oraz osobny dziennik audytu zmian stanu */
+
+pub const AUDYT: &str = "audyt";
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UstawieniaDziennika {
+    pub poziom: String,
+    pub plik: Option<PathBuf>,
+    pub audyt: Option<PathBuf>,
+}
+
+impl Default for UstawieniaDziennika {
+    fn default() -> Self {
+        Self {
+            poziom: "info".to_owned(),
+            plik: None,
+            audyt: Some(PathBuf::from("audyt.log")),
+        }
+    }
+}
+
+fn zapis(ścieżka: &Path, strażnicy: &mut Vec<WorkerGuard>) -> BoxMakeWriter {
+    let katalog = ścieżka
+        .parent()
+        .filter(|katalog| !katalog.as_os_str().is_empty())
+        .map_or_else(|| PathBuf::from("."), PathBuf::from);
+    let plik = ścieżka.file_name().unwrap_or(ścieżka.as_os_str());
+    let (zapis, strażnik) =
+        tracing_appender::non_blocking(tracing_appender::rolling::never(katalog, plik));
+    strażnicy.push(strażnik);
+    BoxMakeWriter::new(zapis)
+}
+
+/// uruchamia dziennik, zwrócone strażniki muszą żyć do końca działania programu.
+pub fn uruchom(ustawienia: &UstawieniaDziennika) -> Result<Vec<WorkerGuard>, Box<dyn Error>> {
+    let mut strażnicy = Vec::new();
+
+    let wyjście = match &ustawienia.plik {
+        Some(ścieżka) => zapis(ścieżka, &mut strażnicy),
+        None => BoxMakeWriter::new(std::io::stdout),
+    };
+    let filtr =
+        EnvFilter::try_new(&ustawienia.poziom)?.add_directive(format!("{AUDYT}=off").parse()?);
+    let główny = fmt::layer().with_writer(wyjście).with_filter(filtr);
+
+    // pusta ścieżka wyłącza dziennik audytu
+    let audyt = ustawienia
+        .audyt
+        .as_ref()
+        .filter(|ścieżka| !ścieżka.as_os_str().is_empty())
+        .map(|ścieżka| {
+            fmt::layer()
+                .json()
+                .with_writer(zapis(ścieżka, &mut strażnicy))
+                .with_filter(Targets::new().with_target(AUDYT, Level::INFO))
+        });
+
+    tracing_subscriber::registry()
+        .with(główny)
+        .with(audyt)
+        .try_init()?;
+
+    Ok(strażnicy)
+}
+
+/// argumenty komendy w postaci, w jakiej widzi je parser, bez prefiksu i nazwy komendy.
+pub fn argumenty(msg: &Message, komenda: &str) -> Vec<Arg> {
+    let mut args = Arg::try_parse(Args::new(&msg.content, &[Delimiter::Single(' ')]))
+        .unwrap_or_default();
+    if let Some(pozycja) = args
+        .iter()
+        .position(|arg| matches!(arg, Arg::Plain(słowo) if słowo == komenda))
+    {
+        args.drain(..=pozycja);
+    }
+    args
+}
+
+pub fn audyt(nadawca: &Nadawca, zmiana: &str, szczegóły: &str) {
+    tracing::info!(
+        target: AUDYT,
+        gildia = ?nadawca.gildia,
+        kanał = %nadawca.kanał,
+        gracz = %nadawca.gracz,
+        zmiana,
+        szczegóły,
+    );
+}
@@ -0,0 +1,123 @@
+use crate::{parser::InvalidArgument, teksty::Mowa};
+use std::{error::Error, fmt};
+
+/* błędy komend, które zgłaszane są użytkownikowi przez hook `after` */
+
+#[derive(Debug)]
+pub enum Błąd {
+    Argument(InvalidArgument),
+    BrakArgumentu(&'static str),
+    NiepoprawnaWartość(&'static str, String),
+    BrakWzorca,
+    BrakRzutu,
+    NieznanyWzorzec(String),
+    BrakBroni,
+    NieznanaBroń(String),
+    NiejednoznacznaBroń(String, Vec<String>),
+    ZajętaNazwa(String),
+    Uzbrojenie(&'static str, usize),
+    NieznanyZestaw(String),
+    BrakOsłon,
+    NieznanaOsłona(String),
+    NiejednoznacznaOsłona(String, Vec<String>),
+    ZajętaNazwaOsłony(String),
+    NieznanySzablon(String),
+    NiepoprawneReguły(String),
+    Magazyn(String),
+    Niedozwolone(&'static str),
+}
+
+impl Błąd {
+    /// wiadomość dla gracza w jego języku, z szablonami gildii. nazwy argumentów oraz
+    /// powody odmowy są kluczami katalogu tekstów.
+    pub fn wiadomość(&self, mowa: &Mowa) -> String {
+        let argument = |nazwa: &str| mowa.tekst(&format!("argument.{nazwa}"));
+        match self {
+            Self::Argument(why) => mowa.wstaw("błąd.argument", &[("argument", why.argument())]),
+            Self::BrakArgumentu(nazwa) => {
+                mowa.wstaw("błąd.brak_argumentu", &[("argument", &argument(nazwa))])
+            }
+            Self::NiepoprawnaWartość(nazwa, wartość) => mowa.wstaw(
+                "błąd.wartość",
+                &[("czego", &argument(nazwa)), ("wartość", wartość)],
+            ),
+            Self::BrakWzorca => mowa.tekst("błąd.brak_wzorca"),
+            Self::BrakRzutu => mowa.tekst("błąd.brak_rzutu"),
+            Self::NieznanyWzorzec(nazwa) => {
+                mowa.wstaw("błąd.nieznany_wzorzec", &[("wzorzec", nazwa)])
+            }
+            Self::BrakBroni => mowa.tekst("błąd.brak_broni"),
+            Self::NieznanaBroń(nazwa) => mowa.wstaw("błąd.nieznana_broń", &[("nazwa", nazwa)]),
+            Self::NiejednoznacznaBroń(nazwa, kandydatki) => mowa.wstaw(
+                "błąd.niejednoznaczna_broń",
+                &[("nazwa", nazwa), ("bronie", &kandydatki.join(", "))],
+            ),
+            Self::ZajętaNazwa(nazwa) => mowa.wstaw("błąd.zajęta_nazwa", &[("nazwa", nazwa)]),
+            Self::Uzbrojenie(powód, limit) => mowa.wstaw(
+                &format!("uzbrojenie.{powód}"),
+                &[("limit", &limit.to_string())],
+            ),
+            Self::NieznanyZestaw(nazwa) => {
+                mowa.wstaw("błąd.nieznany_zestaw", &[("nazwa", nazwa)])
+            }
+            Self::BrakOsłon => mowa.tekst("błąd.brak_osłon"),
+            Self::NieznanaOsłona(nazwa) => {
+                mowa.wstaw("błąd.nieznana_osłona", &[("nazwa", nazwa)])
+            }
+            Self::NiejednoznacznaOsłona(nazwa, kandydatki) => mowa.wstaw(
+                "błąd.niejednoznaczna_osłona",
+                &[("nazwa", nazwa), ("osłony", &kandydatki.join(", "))],
+            ),
+            Self::ZajętaNazwaOsłony(nazwa) => {
+                mowa.wstaw("błąd.zajęta_nazwa_osłony", &[("nazwa", nazwa)])
+            }
+            Self::NieznanySzablon(nazwa) => {
+                mowa.wstaw("błąd.nieznany_szablon", &[("nazwa", nazwa)])
+            }
+            Self::NiepoprawneReguły(_) => mowa.tekst("błąd.reguły"),
+            Self::Magazyn(_) => mowa.tekst("błąd.magazyn"),
+            Self::Niedozwolone(powód) => mowa.tekst(&format!("niedozwolone.{powód}")),
+        }
+    }
+}
+
+impl fmt::Display for Błąd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Argument(why) => write!(f, "{why}"),
+            Self::BrakArgumentu(argument) => write!(f, "brak argumentu {argument}"),
+            Self::NiepoprawnaWartość(argument, wartość) => {
+                write!(f, "niepoprawna wartość {argument}: {wartość}")
+            }
+            Self::BrakWzorca => write!(f, "brak wzorca"),
+            Self::BrakRzutu => write!(f, "brak rzutu do przerzucenia"),
+            Self::NieznanyWzorzec(nazwa) => write!(f, "nieznany wzorzec: {nazwa}"),
+            Self::BrakBroni => write!(f, "brak broni"),
+            Self::NieznanaBroń(nazwa) => write!(f, "nieznana broń: {nazwa}"),
+            Self::NiejednoznacznaBroń(nazwa, kandydatki) => {
+                write!(f, "niejednoznaczna broń: {nazwa} ({})", kandydatki.join(", "))
+            }
+            Self::ZajętaNazwa(nazwa) => write!(f, "zajęta nazwa broni: {nazwa}"),
+            Self::Uzbrojenie(powód, limit) => write!(f, "przekroczone uzbrojenie: {powód} {limit}"),
+            Self::NieznanyZestaw(nazwa) => write!(f, "nieznany zestaw broni: {nazwa}"),
+            Self::BrakOsłon => write!(f, "brak osłon"),
+            Self::NieznanaOsłona(nazwa) => write!(f, "nieznana osłona: {nazwa}"),
+            Self::NiejednoznacznaOsłona(nazwa, kandydatki) => {
+                write!(f, "niejednoznaczna osłona: {nazwa} ({})", kandydatki.join(", "))
+            }
+            Self::ZajętaNazwaOsłony(nazwa) => write!(f, "zajęta nazwa osłony: {nazwa}"),
+            Self::NieznanySzablon(nazwa) => write!(f, "nieznany szablon arsenału: {nazwa}"),
+            Self::NiepoprawneReguły(why) => write!(f, "niepoprawne reguły: {why}"),
+            Self::Magazyn(why) => write!(f, "błąd magazynu: {why}"),
+            Self::Niedozwolone(powód) => write!(f, "niedozwolone: {powód}"),
+        }
+    }
+}
+
+impl Error for Błąd {}
+
+impl From<InvalidArgument> for Błąd {
+    fn from(why: InvalidArgument) -> Self {
+        Self::Argument(why)
+    }
+}
@@ -0,0 +1,1012 @@
+use crate::{
+    dziennik,
+    error::Błąd,
+    komendy::{self, Opcja},
+    parser::Arg,
+    teksty::Mowa,
+    ustawienia,
+    wyniki::{self, Odpowiedź},
+    zug, ArsenałHolder, BronieGraczaHolder, Nadawca, OsłonyGraczaHolder, Szablon,
+    ZestawyGraczaHolder, RZUTY,
+};
+use itertools::Itertools;
+use serde_json::Value;
+use serenity::{
+    builder::{CreateApplicationCommand, CreateApplicationCommandOption, CreateComponents},
+    client::Context,
+    framework::standard::{Args, Delimiter},
+    model::{
+        interactions::{
+            application_command::{
+                ApplicationCommand, ApplicationCommandInteraction,
+                ApplicationCommandInteractionDataOption, ApplicationCommandOptionType,
+            },
+            autocomplete::AutocompleteInteraction,
+            message_component::{ButtonStyle, MessageComponentInteraction},
+            InteractionApplicationCommandCallbackDataFlags, InteractionResponseType,
+        },
+        misc::Mentionable,
+        prelude::UserId,
+    },
+};
+use std::time::Instant;
+use strsim::damerau_levenshtein as dist;
+
+/* polecenia discorda (slash commands) korzystające z tej samej logiki co komendy */
+
+const LIMIT_PODPOWIEDZI: usize = 25;
+const LIMIT_IDENTYFIKATORA: usize = 100;
+const TRWAŁOŚĆ_ZANIKU: usize = 10; // najwyższa trwałość do wyboru przy zaniku narzędzia
+const PONÓW: &str = "ponów";
+const ZANIK: &str = "zanik";
+const ODDAJ: &str = "oddaj";
+const WYBORY: &[&str] = &["wzorzec", "fach", "narzędzie", "drugie_narzędzie", "jakość"];
+
+/* # rejestracja */
+
+fn opis(nazwa: &str) -> String {
+    komendy::Komenda::znajdź(nazwa).map_or_else(String::new, |komenda| komendy::skrót(komenda.opis))
+}
+
+fn wybór<'a>(
+    opcja: &'a mut CreateApplicationCommandOption,
+    nazwa: &str,
+    opis: &str,
+    wartości: &[Opcja],
+) -> &'a mut CreateApplicationCommandOption {
+    opcja
+        .name(nazwa)
+        .description(opis)
+        .kind(ApplicationCommandOptionType::String);
+    for wartość in wartości {
+        opcja.add_string_choice(wartość.wartość(), wartość.wartość());
+    }
+    opcja
+}
+
+fn liczba<'a>(
+    opcja: &'a mut CreateApplicationCommandOption,
+    wzór: &Opcja,
+) -> &'a mut CreateApplicationCommandOption {
+    opcja
+        .name(wzór.wartość())
+        .description(komendy::skrót(wzór.opis))
+        .kind(ApplicationCommandOptionType::Integer)
+}
+
+fn ukryty(opcja: &mut CreateApplicationCommandOption) -> &mut CreateApplicationCommandOption {
+    opcja
+        .name(komendy::UKRYTY.wartość())
+        .description(komendy::skrót(komendy::UKRYTY.opis))
+        .kind(ApplicationCommandOptionType::Boolean)
+}
+
+fn nazwa_broni(
+    opcja: &mut CreateApplicationCommandOption,
+    podpowiadana: bool,
+) -> &mut CreateApplicationCommandOption {
+    opcja
+        .name(komendy::NAZWA.wartość())
+        .description("nazwa broni.")
+        .kind(ApplicationCommandOptionType::String)
+        .required(true)
+        .set_autocomplete(podpowiadana)
+}
+
+fn podkomenda_broni<'a>(
+    opcja: &'a mut CreateApplicationCommandOption,
+    nazwa: &str,
+    opis: &str,
+) -> &'a mut CreateApplicationCommandOption {
+    opcja
+        .name(nazwa)
+        .description(opis)
+        .kind(ApplicationCommandOptionType::SubCommand)
+        .create_sub_option(|o| nazwa_broni(o, true))
+}
+
+/// podkomenda przyjmująca kilka nazw broni oddzielonych spacjami albo wszystkie bronie.
+fn wiele_broni<'a>(
+    opcja: &'a mut CreateApplicationCommandOption,
+    nazwa: &str,
+    opis: &str,
+) -> &'a mut CreateApplicationCommandOption {
+    opcja
+        .name(nazwa)
+        .description(opis)
+        .kind(ApplicationCommandOptionType::SubCommand)
+        .create_sub_option(|o| {
+            nazwa_broni(o, true)
+                .description("nazwy broni oddzielone spacjami.")
+                .required(false)
+        })
+        .create_sub_option(|o| {
+            o.name(komendy::WSZYSTKIE.wartość())
+                .description("wskazuje wszystkie bronie gracza.")
+                .kind(ApplicationCommandOptionType::Boolean)
+        })
+}
+
+fn waga_broni(
+    opcja: &mut CreateApplicationCommandOption,
+    wymagana: bool,
+) -> &mut CreateApplicationCommandOption {
+    opcja
+        .name(komendy::WAGA.wartość())
+        .description("waga broni.")
+        .kind(ApplicationCommandOptionType::String)
+        .required(wymagana)
+        .add_string_choice("lekka", "lekka")
+        .add_string_choice("średnia", "średnia")
+        .add_string_choice("ciężka", "ciężka")
+}
+
+fn zasięg_broni(
+    opcja: &mut CreateApplicationCommandOption,
+    wymagany: bool,
+) -> &mut CreateApplicationCommandOption {
+    opcja
+        .name(komendy::ZASIĘG.wartość())
+        .description("zasięg broni.")
+        .kind(ApplicationCommandOptionType::String)
+        .required(wymagany)
+        .add_string_choice("biała", "biała")
+        .add_string_choice("drzewcowa", "drzewcowa")
+        .add_string_choice("miotająca", "miotająca")
+}
+
+fn nazwa_osłony(
+    opcja: &mut CreateApplicationCommandOption,
+    podpowiadana: bool,
+) -> &mut CreateApplicationCommandOption {
+    opcja
+        .name(komendy::NAZWA.wartość())
+        .description("nazwa osłony.")
+        .kind(ApplicationCommandOptionType::String)
+        .required(true)
+        .set_autocomplete(podpowiadana)
+}
+
+fn podkomenda_osłony<'a>(
+    opcja: &'a mut CreateApplicationCommandOption,
+    nazwa: &str,
+    opis: &str,
+) -> &'a mut CreateApplicationCommandOption {
+    opcja
+        .name(nazwa)
+        .description(opis)
+        .kind(ApplicationCommandOptionType::SubCommand)
+        .create_sub_option(|o| nazwa_osłony(o, true))
+}
+
+fn podkomenda_weź<'a>(
+    opcja: &'a mut CreateApplicationCommandOption,
+    opis: &str,
+) -> &'a mut CreateApplicationCommandOption {
+    opcja
+        .name("weź")
+        .description(opis)
+        .kind(ApplicationCommandOptionType::SubCommand)
+        .create_sub_option(|o| {
+            o.name("szablon")
+                .description("nazwa szablonu z arsenału serwera.")
+                .kind(ApplicationCommandOptionType::String)
+                .required(true)
+                .set_autocomplete(true)
+        })
+        .create_sub_option(|o| {
+            o.name(komendy::NAZWA.wartość())
+                .description("nazwa kopii, domyślnie nazwa szablonu.")
+                .kind(ApplicationCommandOptionType::String)
+        })
+}
+
+fn polecenie_wzorca(p: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    p.name("wzorzec")
+        .description(opis("wzorzec"))
+        .create_option(|o| {
+            // wzorce gildii nie mieszczą się w stałych wyborach, więc są podpowiadane
+            o.name("wzorzec")
+                .description("nowy wzorzec gracza.")
+                .kind(ApplicationCommandOptionType::String)
+                .set_autocomplete(true)
+        })
+}
+
+fn polecenie_broni(p: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    p.name("broń")
+        .description(opis("broń"))
+        .create_option(|o| {
+            o.name("lista")
+                .description("wyświetla bronie gracza.")
+                .kind(ApplicationCommandOptionType::SubCommand)
+        })
+        .create_option(|o| {
+            o.name("dodaj")
+                .description("dodaje nową broń gracza.")
+                .kind(ApplicationCommandOptionType::SubCommand)
+                .create_sub_option(|o| nazwa_broni(o, false))
+                .create_sub_option(|o| waga_broni(o, true))
+                .create_sub_option(|o| zasięg_broni(o, true))
+                .create_sub_option(|o| {
+                    o.name(komendy::CECHY.wartość())
+                        .description("cechy broni oddzielone spacjami, np. `dwuręczna mistrzowska`.")
+                        .kind(ApplicationCommandOptionType::String)
+                })
+        })
+        .create_option(|o| wiele_broni(o, "wybierz", "wybiera wskazane bronie jako aktywne."))
+        .create_option(|o| {
+            wiele_broni(o, "schowaj", "wybiera wskazane bronie jako nieaktywne.")
+        })
+        .create_option(|o| {
+            podkomenda_broni(o, "oddaj", "oddaje wskazaną broń innemu graczowi.")
+                .create_sub_option(|o| {
+                    o.name("gracz")
+                        .description("gracz, który otrzyma broń po jej przyjęciu.")
+                        .kind(ApplicationCommandOptionType::User)
+                        .required(true)
+                })
+        })
+        .create_option(|o| {
+            wiele_broni(o, "zestaw", "zapisuje, usuwa lub wyświetla zestawy broni.")
+                .create_sub_option(|o| {
+                    o.name("nazwa_zestawu")
+                        .description("nazwa zestawu. bez broni zestaw zostanie usunięty.")
+                        .kind(ApplicationCommandOptionType::String)
+                        .set_autocomplete(true)
+                })
+        })
+        .create_option(|o| {
+            podkomenda_broni(o, "zmień", "zmienia wagę lub zasięg wskazanej broni.")
+                .create_sub_option(|o| waga_broni(o, false))
+                .create_sub_option(|o| zasięg_broni(o, false))
+        })
+        .create_option(|o| {
+            o.name("przemianuj")
+                .description("nadaje wskazanej broni nową nazwę.")
+                .kind(ApplicationCommandOptionType::SubCommand)
+                .create_sub_option(|o| {
+                    o.name("nowa")
+                        .description("nowa nazwa broni.")
+                        .kind(ApplicationCommandOptionType::String)
+                        .required(true)
+                })
+                .create_sub_option(|o| nazwa_broni(o, true))
+        })
+        .create_option(|o| podkomenda_broni(o, "usuń", "usuwa wskazaną broń z wyposażenia."))
+        .create_option(|o| podkomenda_weź(o, "bierze kopię broni z arsenału serwera."))
+}
+
+fn polecenie_osłony(p: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    p.name("osłona")
+        .description(opis("osłona"))
+        .create_option(|o| {
+            o.name("lista")
+                .description("wyświetla osłony gracza.")
+                .kind(ApplicationCommandOptionType::SubCommand)
+        })
+        .create_option(|o| {
+            o.name("dodaj")
+                .description("dodaje nową osłonę gracza.")
+                .kind(ApplicationCommandOptionType::SubCommand)
+                .create_sub_option(|o| nazwa_osłony(o, false))
+                .create_sub_option(|o| {
+                    o.name(komendy::RODZAJ.wartość())
+                        .description("rodzaj osłony.")
+                        .kind(ApplicationCommandOptionType::String)
+                        .required(true)
+                        .add_string_choice("pancerz", "pancerz")
+                        .add_string_choice("tarcza", "tarcza")
+                })
+                .create_sub_option(|o| {
+                    o.name("trwałość")
+                        .description("trwałość osłony.")
+                        .kind(ApplicationCommandOptionType::Integer)
+                        .required(true)
+                })
+                .create_sub_option(|o| {
+                    wybór(
+                        o,
+                        "jakość",
+                        "jakość osłony, domyślnie przyzwoita.",
+                        &[komendy::ZNAKOMITA, komendy::PRZYZWOITA, komendy::KIEPSKA],
+                    )
+                })
+        })
+        .create_option(|o| podkomenda_osłony(o, "załóż", "zakłada wskazaną osłonę."))
+        .create_option(|o| podkomenda_osłony(o, "zdejmij", "zdejmuje wskazaną osłonę."))
+        .create_option(|o| podkomenda_osłony(o, "usuń", "usuwa wskazaną osłonę z wyposażenia."))
+        .create_option(|o| podkomenda_weź(o, "bierze kopię osłony z arsenału serwera."))
+}
+
+fn polecenie_próby(p: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    p.name("próba")
+        .description(opis("próba"))
+        .create_option(|o| {
+            wybór(o, "fach", "przeszkolenie, domyślnie zielone.", &[komendy::SZKOLONY, komendy::BIEGŁY])
+        })
+        .create_option(|o| {
+            wybór(
+                o,
+                "narzędzie",
+                "jakość użytego narzędzia.",
+                &[komendy::ZNAKOMITA, komendy::PRZYZWOITA, komendy::KIEPSKA],
+            )
+        })
+        .create_option(|o| {
+            wybór(
+                o,
+                "drugie_narzędzie",
+                "jakość drugiego narzędzia, dokładającego kolejną kość.",
+                &[komendy::ZNAKOMITA, komendy::PRZYZWOITA, komendy::KIEPSKA],
+            )
+        })
+        .create_option(|o| liczba(o, &komendy::PREMIA))
+        .create_option(|o| liczba(o, &komendy::KARA))
+        .create_option(pomocnik)
+        .create_option(ukryty)
+}
+
+fn pomocnik(opcja: &mut CreateApplicationCommandOption) -> &mut CreateApplicationCommandOption {
+    opcja
+        .name("pomocnik")
+        .description("gracz pomagający w próbie kośćmi swojego wzorca.")
+        .kind(ApplicationCommandOptionType::User)
+}
+
+fn polecenie_bitwy(p: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    p.name("bitwa")
+        .description(opis("bitwa"))
+        .create_option(|o| liczba(o, &komendy::PLUS))
+        .create_option(|o| liczba(o, &komendy::MINUS))
+        .create_option(zestaw_bitwy)
+        .create_option(ukryty)
+}
+
+fn zestaw_bitwy(opcja: &mut CreateApplicationCommandOption) -> &mut CreateApplicationCommandOption {
+    opcja
+        .name(komendy::ZESTAW.wartość())
+        .description("zestaw broni użyty zamiast broni aktywnych.")
+        .kind(ApplicationCommandOptionType::String)
+        .set_autocomplete(true)
+}
+
+fn polecenie_przerzutu(p: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    p.name("przerzuć")
+        .description(opis("przerzuć"))
+        .create_option(|o| {
+            o.name("kości")
+                .description("numery przerzucanych kości oddzielone spacjami, np. `2 5`.")
+                .kind(ApplicationCommandOptionType::String)
+        })
+        .create_option(|o| {
+            o.name(komendy::KSIĘŻYCE.wartość())
+                .description(komendy::skrót(komendy::KSIĘŻYCE.opis))
+                .kind(ApplicationCommandOptionType::Boolean)
+        })
+        .create_option(ukryty)
+}
+
+fn polecenie_puli(p: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    p.name("pula")
+        .description(opis("pula"))
+        .create_option(|o| {
+            o.name("próba")
+                .description("kości próby.")
+                .kind(ApplicationCommandOptionType::SubCommand)
+                .create_sub_option(|o| {
+                    wybór(
+                        o,
+                        "fach",
+                        "przeszkolenie, domyślnie zielone.",
+                        &[komendy::SZKOLONY, komendy::BIEGŁY],
+                    )
+                })
+                .create_sub_option(|o| {
+                    wybór(
+                        o,
+                        "narzędzie",
+                        "jakość użytego narzędzia.",
+                        &[komendy::ZNAKOMITA, komendy::PRZYZWOITA, komendy::KIEPSKA],
+                    )
+                })
+                .create_sub_option(|o| {
+                    wybór(
+                        o,
+                        "drugie_narzędzie",
+                        "jakość drugiego narzędzia, dokładającego kolejną kość.",
+                        &[komendy::ZNAKOMITA, komendy::PRZYZWOITA, komendy::KIEPSKA],
+                    )
+                })
+                .create_sub_option(|o| liczba(o, &komendy::PREMIA))
+                .create_sub_option(|o| liczba(o, &komendy::KARA))
+                .create_sub_option(pomocnik)
+        })
+        .create_option(|o| {
+            o.name("bitwa")
+                .description("kości bitwy z aktywnymi brońmi albo bronią zestawu.")
+                .kind(ApplicationCommandOptionType::SubCommand)
+                .create_sub_option(|o| liczba(o, &komendy::PLUS))
+                .create_sub_option(|o| liczba(o, &komendy::MINUS))
+                .create_sub_option(zestaw_bitwy)
+        })
+}
+
+fn polecenie_pomocy(p: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    p.name("pomagam")
+        .description(opis("pomagam"))
+        .create_option(|o| {
+            o.name("gracz")
+                .description("gracz, któremu pomożesz w jego najbliższej próbie.")
+                .kind(ApplicationCommandOptionType::User)
+                .required(true)
+        })
+}
+
+fn polecenie_zaniku(p: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    p.name("zanik")
+        .description(opis("zanik"))
+        .create_option(|o| {
+            o.name("trwałość")
+                .description("aktualna trwałość sprzętu, zbędna dla osłony.")
+                .kind(ApplicationCommandOptionType::Integer)
+        })
+        .create_option(|o| {
+            o.name(komendy::NAZWA.wartość())
+                .description("osłona gracza, której trwałość zostanie zapisana.")
+                .kind(ApplicationCommandOptionType::String)
+                .set_autocomplete(true)
+        })
+        .create_option(|o| {
+            wybór(
+                o,
+                "jakość",
+                "jakość sprzętu, domyślnie przyzwoita.",
+                &[komendy::ZNAKOMITA, komendy::PRZYZWOITA, komendy::KIEPSKA],
+            )
+        })
+        .create_option(ukryty)
+}
+
+/// rejestruje polecenia globalnie, zastępując poprzednio zarejestrowane.
+pub async fn zarejestruj(ctx: &Context) -> serenity::Result<Vec<ApplicationCommand>> {
+    ApplicationCommand::set_global_application_commands(&ctx.http, |polecenia| {
+        polecenia
+            .create_application_command(polecenie_wzorca)
+            .create_application_command(polecenie_broni)
+            .create_application_command(polecenie_osłony)
+            .create_application_command(polecenie_próby)
+            .create_application_command(polecenie_pomocy)
+            .create_application_command(polecenie_bitwy)
+            .create_application_command(polecenie_przerzutu)
+            .create_application_command(polecenie_puli)
+            .create_application_command(polecenie_zaniku)
+    })
+    .await
+}
+
+/* # wykonanie */
+
+/// opcje polecenia w postaci, jaką dałby parser dla odpowiadającej komendy.
+fn na_argumenty(opcja: &ApplicationCommandInteractionDataOption) -> Vec<Arg> {
+    let wartość = opcja.value.as_ref().map(|wartość| match wartość {
+        Value::String(tekst) => tekst.to_lowercase(),
+        inna => inna.to_string(),
+    });
+    match (opcja.kind, opcja.name.as_str(), wartość) {
+        (ApplicationCommandOptionType::SubCommand, nazwa, _) => {
+            // lista odpowiada komendzie użytej bez argumentu
+            let podkomenda = (nazwa != "lista").then(|| Arg::Plain(nazwa.to_owned()));
+            podkomenda
+                .into_iter()
+                .chain(opcja.options.iter().flat_map(na_argumenty))
+                .collect()
+        }
+        (ApplicationCommandOptionType::Boolean, nazwa, Some(wartość)) if wartość == "true" => {
+            vec![Arg::Long(nazwa.to_owned(), Vec::new())]
+        }
+        (ApplicationCommandOptionType::Boolean, ..) => Vec::new(),
+        // gracz podawany jest tak, jak wzmianka w komendzie
+        (ApplicationCommandOptionType::User, _, Some(wartość)) => {
+            vec![Arg::Plain(format!("<@{wartość}>"))]
+        }
+        (_, "trwałość" | "szablon" | "nazwa_zestawu" | "kości", Some(wartość)) => {
+            vec![Arg::Plain(wartość)]
+        }
+        (_, nazwa, Some(wartość)) if WYBORY.contains(&nazwa) => {
+            vec![Arg::Long(wartość, Vec::new())]
+        }
+        (_, nazwa, Some(wartość)) => vec![Arg::Long(nazwa.to_owned(), vec![wartość])],
+        (_, _, None) => Vec::new(),
+    }
+}
+
+/// wykonuje polecenie, zwracając odpowiedź oraz to, czy ma być widoczna tylko dla gracza.
+async fn wykonaj(
+    ctx: &Context,
+    nadawca: &Nadawca,
+    polecenie: &str,
+    args: &[Arg],
+) -> Result<(Odpowiedź, bool), Błąd> {
+    if RZUTY.contains(&polecenie) && !ustawienia::rzuty_dozwolone(ctx, nadawca).await {
+        return Err(Błąd::Niedozwolone("kanał"));
+    }
+    match polecenie {
+        "wzorzec" => Ok((crate::obsłuż_wzorzec(ctx, nadawca, args).await?.into(), false)),
+        "broń" if matches!(args.first(), Some(Arg::Plain(cmd)) if cmd == "oddaj") => {
+            Ok((crate::broń_oddaj(ctx, nadawca, args).await?, false))
+        }
+        "broń" => Ok((crate::obsłuż_broń(ctx, nadawca, args).await?.into(), false)),
+        "osłona" => Ok((crate::obsłuż_osłonę(ctx, nadawca, args).await?.into(), false)),
+        "próba" => {
+            let ukryty = crate::ukryty(ctx, nadawca, args).await?;
+            Ok((crate::obsłuż_próbę(ctx, nadawca, args).await?, ukryty))
+        }
+        "bitwa" => {
+            let ukryty = crate::ukryty(ctx, nadawca, args).await?;
+            Ok((crate::obsłuż_bitwę(ctx, nadawca, args).await?, ukryty))
+        }
+        "przerzuć" => {
+            let ukryty = crate::ukryty(ctx, nadawca, args).await?;
+            Ok((crate::obsłuż_przerzut(ctx, nadawca, args).await?, ukryty))
+        }
+        "pomagam" => Ok((crate::obsłuż_pomoc(ctx, nadawca, args).await?.into(), false)),
+        "pula" => Ok((crate::obsłuż_pulę(ctx, nadawca, args).await?.into(), false)),
+        "zanik" => {
+            let ukryty = crate::ukryty(ctx, nadawca, args).await?;
+            Ok((crate::obsłuż_zanik(ctx, nadawca, args).await?.into(), ukryty))
+        }
+        _ => Err(Błąd::NiepoprawnaWartość("polecenie", polecenie.to_owned())),
+    }
+}
+
+pub async fn polecenie(ctx: &Context, polecenie: &ApplicationCommandInteraction) {
+    let start = Instant::now();
+    let nadawca = Nadawca {
+        gracz: polecenie.user.id,
+        gildia: polecenie.guild_id,
+        kanał: polecenie.channel_id,
+    };
+    let mowa = ustawienia::mowa(ctx, &nadawca).await;
+    let nazwa = polecenie.data.name.as_str();
+    let argumenty = polecenie
+        .data
+        .options
+        .iter()
+        .flat_map(na_argumenty)
+        .collect::<Vec<_>>();
+
+    // błędy widzi wyłącznie gracz, który wywołał polecenie
+    let (treść, prywatna, powtarzalny) = match wykonaj(ctx, &nadawca, nazwa, &argumenty).await {
+        Ok((treść, prywatna)) => {
+            tracing::info!(
+                gildia = ?nadawca.gildia,
+                kanał = %nadawca.kanał,
+                gracz = %nadawca.gracz,
+                polecenie = nazwa,
+                ?argumenty,
+                wynik = "sukces",
+                opóźnienie_ms = start.elapsed().as_millis(),
+            );
+            (treść, prywatna, !prywatna && RZUTY.contains(&nazwa))
+        }
+        Err(why) => {
+            tracing::warn!(
+                gildia = ?nadawca.gildia,
+                kanał = %nadawca.kanał,
+                gracz = %nadawca.gracz,
+                polecenie = nazwa,
+                ?argumenty,
+                wynik = "błąd",
+                opóźnienie_ms = start.elapsed().as_millis(),
+                błąd = %why,
+            );
+            (Odpowiedź::from(why.wiadomość(&mowa)), true, false)
+        }
+    };
+
+    let odpowiedź = polecenie
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| {
+                    treść.dane(d);
+                    if prywatna {
+                        d.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+                    }
+                    if powtarzalny {
+                        d.components(|c| {
+                            składniki(c, &mowa, nadawca.gracz, nazwa, &argumenty)
+                        });
+                    }
+                    d
+                })
+        })
+        .await;
+    if let Err(why) = odpowiedź {
+        tracing::error!(błąd = ?why, "nie udało się odpowiedzieć na polecenie");
+    }
+    if let Some(obraz) = treść.obraz.as_deref().filter(|_| !prywatna) {
+        let wysłany = polecenie
+            .create_followup_message(&ctx.http, |f| f.add_file(wyniki::załącznik(obraz)))
+            .await;
+        if let Err(why) = wysłany {
+            tracing::error!(błąd = ?why, "nie udało się wysłać obrazu rzutu");
+        }
+    }
+}
+
+/* # przyciski */
+
+/// rzut zapisany w identyfikatorze składnika, by można go było powtórzyć po restarcie bota.
+struct Rzut {
+    gracz: UserId,
+    komenda: String,
+    args: Vec<Arg>,
+}
+
+impl Rzut {
+    fn z_identyfikatora(identyfikator: &str, wartości: &[String]) -> Result<Self, Błąd> {
+        let niepoprawny = || Błąd::NiepoprawnaWartość("składnik", identyfikator.to_owned());
+        let odczytaj = |zapis: &str| Arg::try_parse(Args::new(zapis, &[Delimiter::Single(' ')]));
+        let mut części = identyfikator.splitn(3, ':');
+        let (rodzaj, gracz, reszta) = match (części.next(), części.next(), części.next()) {
+            (Some(rodzaj), Some(gracz), Some(reszta)) => (rodzaj, gracz, reszta),
+            _ => return Err(niepoprawny()),
+        };
+        let gracz = UserId(gracz.parse().map_err(|_| niepoprawny())?);
+        match rodzaj {
+            PONÓW => {
+                let (komenda, args) = reszta.split_once(':').ok_or_else(niepoprawny)?;
+                Ok(Self {
+                    gracz,
+                    komenda: komenda.to_owned(),
+                    args: odczytaj(args)?,
+                })
+            }
+            ZANIK => {
+                let trwałość = wartości.first().ok_or(Błąd::BrakArgumentu("trwałość"))?;
+                Ok(Self {
+                    gracz,
+                    komenda: "zanik".to_owned(),
+                    args: odczytaj(&format!("{trwałość} {reszta}"))?,
+                })
+            }
+            _ => Err(niepoprawny()),
+        }
+    }
+}
+
+/// przycisk powtórzenia rzutu oraz, dla próby z narzędziami, wybór zaniku każdego z nich.
+pub fn składniki<'a>(
+    c: &'a mut CreateComponents,
+    mowa: &Mowa,
+    gracz: UserId,
+    komenda: &str,
+    args: &[Arg],
+) -> &'a mut CreateComponents {
+    let ponów = format!("{PONÓW}:{gracz}:{komenda}:{}", args.iter().join(" "));
+    if ponów.chars().count() <= LIMIT_IDENTYFIKATORA {
+        c.create_action_row(|r| {
+            r.create_button(|b| {
+                b.style(ButtonStyle::Secondary)
+                    .label(mowa.tekst("przyciski.ponów"))
+                    .custom_id(ponów)
+            })
+        });
+    }
+    if komenda != "próba" {
+        return c;
+    }
+    let narzędzia = args
+        .iter()
+        .filter_map(|arg| Some((arg, zug::Narzędzie::try_parse(arg)?)))
+        .unique_by(|(_, jakość)| jakość.nazwa())
+        .collect::<Vec<_>>();
+    for (narzędzie, jakość) in &narzędzia {
+        // przy kilku narzędziach wybory rozróżnia jakość
+        let opis = if narzędzia.len() == 1 {
+            mowa.tekst("przyciski.zanik")
+        } else {
+            let jakość = mowa.tekst(&format!("jakość.{}", jakość.nazwa()));
+            mowa.wstaw("przyciski.zanik_jakości", &[("jakość", &jakość)])
+        };
+        c.create_action_row(|r| {
+            r.create_select_menu(|s| {
+                s.custom_id(format!("{ZANIK}:{gracz}:{narzędzie}"))
+                    .placeholder(&opis)
+                    .options(|o| {
+                        for trwałość in 1..=TRWAŁOŚĆ_ZANIKU {
+                            o.create_option(|o| {
+                                o.label(mowa.wstaw(
+                                    "przyciski.trwałość",
+                                    &[("trwałość", &trwałość.to_string())],
+                                ))
+                                    .value(trwałość)
+                            });
+                        }
+                        o
+                    })
+            })
+        });
+    }
+    c
+}
+
+/// przyciski, którymi gracz przyjmuje albo odrzuca oddawaną mu broń.
+pub fn przyciski_oddania(
+    mowa: &Mowa,
+    dawca: UserId,
+    broń: u64,
+    odbiorca: UserId,
+) -> CreateComponents {
+    let mut c = CreateComponents::default();
+    c.create_action_row(|r| {
+        r.create_button(|b| {
+            b.style(ButtonStyle::Primary)
+                .label(mowa.tekst("przyciski.przyjmij"))
+                .custom_id(format!("{ODDAJ}:{dawca}:{broń}:{odbiorca}:tak"))
+        })
+        .create_button(|b| {
+            b.style(ButtonStyle::Secondary)
+                .label(mowa.tekst("przyciski.odrzuć"))
+                .custom_id(format!("{ODDAJ}:{dawca}:{broń}:{odbiorca}:nie"))
+        })
+    });
+    c
+}
+
+/// przyjęcie broni może potwierdzić tylko odbiorca, a odrzucić również oddający.
+async fn wykonaj_oddanie(
+    ctx: &Context,
+    nadawca: &Nadawca,
+    mowa: &Mowa,
+    identyfikator: &str,
+) -> Result<String, Błąd> {
+    let niepoprawny = || Błąd::NiepoprawnaWartość("składnik", identyfikator.to_owned());
+    let części = identyfikator.split(':').skip(1).collect::<Vec<_>>();
+    let [dawca, broń, odbiorca, decyzja] = części.as_slice() else {
+        return Err(niepoprawny());
+    };
+    let dawca = UserId(dawca.parse().map_err(|_| niepoprawny())?);
+    let broń = broń.parse().map_err(|_| niepoprawny())?;
+    let odbiorca = UserId(odbiorca.parse().map_err(|_| niepoprawny())?);
+    match *decyzja {
+        "tak" if nadawca.gracz == odbiorca => {
+            crate::przekaż_broń(ctx, nadawca, mowa, dawca, broń).await
+        }
+        "nie" if nadawca.gracz == odbiorca || nadawca.gracz == dawca => {
+            dziennik::audyt(nadawca, "broń.odrzucono", &format!("{dawca} -> {odbiorca}"));
+            Ok(mowa.tekst("broń.odrzucono"))
+        }
+        "tak" | "nie" => Err(Błąd::Niedozwolone("oddanie")),
+        _ => Err(niepoprawny()),
+    }
+}
+
+/// odpowiedź na przycisk oddania zastępuje ofertę, usuwając z niej przyciski.
+async fn oddanie(ctx: &Context, nadawca: &Nadawca, składnik: &MessageComponentInteraction) {
+    let mowa = ustawienia::mowa(ctx, nadawca).await;
+    let wynik = wykonaj_oddanie(ctx, nadawca, &mowa, &składnik.data.custom_id).await;
+    if let Err(why) = &wynik {
+        tracing::warn!(
+            gildia = ?nadawca.gildia,
+            kanał = %nadawca.kanał,
+            gracz = %nadawca.gracz,
+            składnik = %składnik.data.custom_id,
+            wynik = "błąd",
+            błąd = %why,
+        );
+    }
+    let odpowiedź = składnik
+        .create_interaction_response(&ctx.http, |r| match &wynik {
+            Ok(treść) => r
+                .kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|d| d.content(treść).components(|c| c)),
+            Err(why) => r
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| {
+                    d.content(why.wiadomość(&mowa))
+                        .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                }),
+        })
+        .await;
+    if let Err(why) = odpowiedź {
+        tracing::error!(błąd = ?why, "nie udało się odpowiedzieć na oddanie broni");
+    }
+}
+
+async fn wykonaj_składnik(
+    ctx: &Context,
+    nadawca: &Nadawca,
+    składnik: &MessageComponentInteraction,
+) -> Result<(Rzut, Odpowiedź), Błąd> {
+    let rzut = Rzut::z_identyfikatora(&składnik.data.custom_id, &składnik.data.values)?;
+    if rzut.gracz != nadawca.gracz && !ustawienia::czy_mg(ctx, nadawca).await {
+        return Err(Błąd::Niedozwolone("ponowienie"));
+    }
+    // rzut wykonywany jest tak, jakby ponowił go sam rzucający
+    let rzucający = Nadawca {
+        gracz: rzut.gracz,
+        gildia: nadawca.gildia,
+        kanał: nadawca.kanał,
+    };
+    let (wynik, _) = wykonaj(ctx, &rzucający, &rzut.komenda, &rzut.args).await?;
+    Ok((rzut, wynik))
+}
+
+pub async fn składnik(ctx: &Context, składnik: &MessageComponentInteraction) {
+    let nadawca = Nadawca {
+        gracz: składnik.user.id,
+        gildia: składnik.guild_id,
+        kanał: składnik.channel_id,
+    };
+    if składnik.data.custom_id.starts_with(ODDAJ) {
+        return oddanie(ctx, &nadawca, składnik).await;
+    }
+    let mowa = ustawienia::mowa(ctx, &nadawca).await;
+
+    let (treść, rzut) = match wykonaj_składnik(ctx, &nadawca, składnik).await {
+        Ok((rzut, mut wynik)) => {
+            tracing::info!(
+                gildia = ?nadawca.gildia,
+                kanał = %nadawca.kanał,
+                gracz = %nadawca.gracz,
+                rzucający = %rzut.gracz,
+                komenda = %rzut.komenda,
+                argumenty = ?rzut.args,
+                wynik = "sukces",
+                "ponowiono rzut"
+            );
+            wynik.treść = format!("{} {}", rzut.gracz.mention(), wynik.treść)
+                .trim_end()
+                .to_owned();
+            (wynik, Some(rzut))
+        }
+        Err(why) => {
+            tracing::warn!(
+                gildia = ?nadawca.gildia,
+                kanał = %nadawca.kanał,
+                gracz = %nadawca.gracz,
+                składnik = %składnik.data.custom_id,
+                wynik = "błąd",
+                błąd = %why,
+            );
+            (Odpowiedź::from(why.wiadomość(&mowa)), None)
+        }
+    };
+
+    let odpowiedź = składnik
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| {
+                    treść.dane(d);
+                    match &rzut {
+                        Some(rzut) => {
+                            d.components(|c| {
+                                składniki(c, &mowa, rzut.gracz, &rzut.komenda, &rzut.args)
+                            });
+                        }
+                        None => {
+                            d.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+                        }
+                    }
+                    d
+                })
+        })
+        .await;
+    if let Err(why) = odpowiedź {
+        tracing::error!(błąd = ?why, "nie udało się odpowiedzieć na składnik");
+    }
+    if let Some(obraz) = treść.obraz.as_deref() {
+        let wysłany = składnik
+            .create_followup_message(&ctx.http, |f| f.add_file(wyniki::załącznik(obraz)))
+            .await;
+        if let Err(why) = wysłany {
+            tracing::error!(błąd = ?why, "nie udało się wysłać obrazu rzutu");
+        }
+    }
+}
+
+/* # podpowiedzi */
+
+/// nazwa podpowiadanej opcji oraz wpisana w nią dotąd wartość.
+fn wpisane(opcje: &[ApplicationCommandInteractionDataOption]) -> Option<(&str, String)> {
+    opcje.iter().find_map(|opcja| {
+        if opcja.focused {
+            let wartość = opcja.value.as_ref()?.as_str()?.to_lowercase();
+            Some((opcja.name.as_str(), wartość))
+        } else {
+            wpisane(&opcja.options)
+        }
+    })
+}
+
+/// wzorce podstawowe oraz własne wzorce z reguł gildii.
+async fn nazwy_wzorców(ctx: &Context, nadawca: &Nadawca) -> Vec<String> {
+    let reguły = ustawienia::reguły(ctx, nadawca).await;
+    [komendy::CHOJRAK, komendy::SZELMA]
+        .iter()
+        .map(|opcja| opcja.wartość().to_owned())
+        .chain(reguły.wzorce.własne.keys().cloned())
+        .collect()
+}
+
+async fn nazwy_broni(ctx: &Context, nadawca: &Nadawca) -> Vec<String> {
+    let data = ctx.data.read().await;
+    data.get::<BronieGraczaHolder>()
+        .and_then(|holder| holder.get(&nadawca.gracz))
+        .into_iter()
+        .flatten()
+        .map(|broń| broń.nazwa.to_string())
+        .collect()
+}
+
+async fn nazwy_osłon(ctx: &Context, nadawca: &Nadawca) -> Vec<String> {
+    let data = ctx.data.read().await;
+    data.get::<OsłonyGraczaHolder>()
+        .and_then(|holder| holder.get(&nadawca.gracz))
+        .into_iter()
+        .flatten()
+        .map(|osłona| osłona.nazwa.to_string())
+        .collect()
+}
+
+async fn nazwy_zestawów(ctx: &Context, nadawca: &Nadawca) -> Vec<String> {
+    let data = ctx.data.read().await;
+    data.get::<ZestawyGraczaHolder>()
+        .and_then(|holder| holder.get(&nadawca.gracz))
+        .into_iter()
+        .flat_map(|zestawy| zestawy.keys().cloned())
+        .collect()
+}
+
+/// szablony broni lub osłon z arsenału gildii.
+async fn nazwy_szablonów(ctx: &Context, nadawca: &Nadawca, broń: bool) -> Vec<String> {
+    let data = ctx.data.read().await;
+    data.get::<ArsenałHolder>()
+        .zip(nadawca.gildia)
+        .and_then(|(holder, gildia)| holder.get(&gildia))
+        .into_iter()
+        .flatten()
+        .filter(|(_, szablon)| matches!(szablon, Szablon::Broń(_)) == broń)
+        .map(|(nazwa, _)| nazwa.clone())
+        .collect()
+}
+
+/// podpowiada wzorce w poleceniu `wzorzec`, nazwy osłon gracza w poleceniach `osłona`
+/// i `zanik`, nazwy broni gracza w poleceniu `broń` oraz szablony arsenału przy `weź`.
+pub async fn podpowiedz(ctx: &Context, podpowiedź: &AutocompleteInteraction) {
+    let nadawca = Nadawca {
+        gracz: podpowiedź.user.id,
+        gildia: podpowiedź.guild_id,
+        kanał: podpowiedź.channel_id,
+    };
+    let (opcja, wpisane) = wpisane(&podpowiedź.data.options).unwrap_or_default();
+    let mut nazwy = match (podpowiedź.data.name.as_str(), opcja) {
+        (polecenie, "szablon") => nazwy_szablonów(ctx, &nadawca, polecenie == "broń").await,
+        (_, "zestaw" | "nazwa_zestawu") => nazwy_zestawów(ctx, &nadawca).await,
+        ("wzorzec", _) => nazwy_wzorców(ctx, &nadawca).await,
+        ("osłona" | "zanik", _) => nazwy_osłon(ctx, &nadawca).await,
+        _ => nazwy_broni(ctx, &nadawca).await,
+    };
+    // przy kilku nazwach oddzielonych spacjami podpowiadana jest ostatnia
+    let (poprzednie, ostatnia) = wpisane.rsplit_once(' ').unwrap_or(("", &wpisane));
+    nazwy.retain(|nazwa| nazwa.starts_with(ostatnia) || dist(nazwa, ostatnia) < 3);
+    nazwy.sort();
+    nazwy.truncate(LIMIT_PODPOWIEDZI);
+    if !poprzednie.is_empty() {
+        nazwy = nazwy.into_iter().map(|nazwa| format!("{poprzednie} {nazwa}")).collect();
+    }
+
+    let odpowiedź = podpowiedź
+        .create_autocomplete_response(&ctx.http, |r| {
+            for nazwa in &nazwy {
+                r.add_string_choice(nazwa, nazwa);
+            }
+            r
+        })
+        .await;
+    if let Err(why) = odpowiedź {
+        tracing::error!(błąd = ?why, "nie udało się podpowiedzieć nazw");
+    }
+}
@@ -0,0 +1,756 @@
+use crate::{parser::Arg, teksty, ustawienia::Język};
+use itertools::Itertools;
+use strsim::damerau_levenshtein as dist;
+
+/* metadane komend, z których korzystają zarówno parsery, jak i wsparcie */
+
+pub const TOLERANCJA: usize = 3;
+/// opcje wybierające wzorzec, fach lub narzędzie od początku znosiły więcej literówek.
+pub const TOLERANCJA_WARTOŚCI: usize = 4;
+pub const LIMIT_WIADOMOŚCI: usize = 1900; // zapas na wzmiankę dodawaną przez odpowiedź
+const LIMIT_OPISU: usize = 100;
+
+/* # opcje */
+
+pub struct Opcja {
+    pub krótkie: &'static [char],
+    pub długie: &'static [&'static str],
+    /// długie opcje pasują do słów odległych o mniej niż tyle literówek.
+    pub tolerancja: usize,
+    pub parametr: Option<&'static str>,
+    pub opis: &'static str,
+}
+
+impl Opcja {
+    pub fn dopasuj<'a>(&self, arg: &'a Arg) -> Option<&'a [String]> {
+        match arg {
+            Arg::Short(ch, params) if self.krótkie.contains(ch) => Some(params),
+            Arg::Long(word, params)
+                if self
+                    .długie
+                    .iter()
+                    .any(|długa| dist(word, długa) < self.tolerancja) =>
+            {
+                Some(params)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn pasuje(&self, arg: &Arg) -> bool {
+        self.dopasuj(arg).is_some()
+    }
+
+    /// nazwa opcji w poleceniach, gdzie wybierana jest jako jedna z wartości.
+    pub fn wartość(&self) -> &'static str {
+        self.długie[0]
+    }
+
+    fn nagłówek(&self) -> String {
+        let parametr = self.parametr.map(|p| format!(" {p}")).unwrap_or_default();
+        self.krótkie
+            .iter()
+            .map(|ch| format!("-{ch}{parametr}"))
+            .chain(self.długie.iter().map(|długa| format!("--{długa}{parametr}")))
+            .join(", ")
+    }
+}
+
+pub const CHOJRAK: Opcja = Opcja {
+    krótkie: &['c'],
+    długie: &["chojrak", "chojraczka"],
+    tolerancja: TOLERANCJA_WARTOŚCI,
+    parametr: None,
+    opis: "ustawia wzorzec chojraka.",
+};
+
+pub const SZELMA: Opcja = Opcja {
+    krótkie: &['s', 'w'],
+    długie: &["szelma"],
+    tolerancja: TOLERANCJA_WARTOŚCI,
+    parametr: None,
+    opis: "ustawia wzorzec szelmy.",
+};
+
+pub const NAZWA: Opcja = Opcja {
+    krótkie: &['n'],
+    długie: &["nazwa"],
+    tolerancja: TOLERANCJA,
+    parametr: Some("STR"),
+    opis: "identyfikator broni lub osłony. znaki STR reprezentują ciąg znaków bez białych lub specjalnych innych niż '_'.",
+};
+
+pub const NOWA: Opcja = Opcja {
+    krótkie: &[],
+    długie: &["nowa"],
+    tolerancja: TOLERANCJA,
+    parametr: Some("STR"),
+    opis: "nowa nazwa broni.",
+};
+
+pub const WAGA: Opcja = Opcja {
+    krótkie: &['w'],
+    długie: &["waga"],
+    tolerancja: TOLERANCJA,
+    parametr: Some("W"),
+    opis: "ustawia wagę broni. znak W reprezentuje wagę spośród (L lekka, Ś średnia, C ciężka).",
+};
+
+pub const ZASIĘG: Opcja = Opcja {
+    krótkie: &['z'],
+    długie: &["zasięg"],
+    tolerancja: TOLERANCJA,
+    parametr: Some("Z"),
+    opis: "ustawia zasięg broni. znak Z reprezentuje zasięg spośród (B biała, D drzewcowa, M miotająca).",
+};
+
+pub const CECHY: Opcja = Opcja {
+    krótkie: &['c'],
+    długie: &["cechy", "cecha"],
+    tolerancja: TOLERANCJA,
+    parametr: Some("C…"),
+    opis: "nadaje broni cechy zmieniające jej kość według reguł. znaki C reprezentują cechy spośród (D dwuręczna, P palna, I improwizowana, M mistrzowska).",
+};
+
+pub const RODZAJ: Opcja = Opcja {
+    krótkie: &['r'],
+    długie: &["rodzaj"],
+    tolerancja: TOLERANCJA,
+    parametr: Some("R"),
+    opis: "ustawia rodzaj osłony. znak R reprezentuje rodzaj spośród (P pancerz, T tarcza).",
+};
+
+pub const SZKOLONY: Opcja = Opcja {
+    krótkie: &['s'],
+    długie: &["szkolony", "szkolona"],
+    tolerancja: TOLERANCJA_WARTOŚCI,
+    parametr: None,
+    opis: "ustawia przeszkolenie podstawowe.",
+};
+
+pub const BIEGŁY: Opcja = Opcja {
+    krótkie: &['b'],
+    długie: &["biegły", "biegła"],
+    tolerancja: TOLERANCJA_WARTOŚCI,
+    parametr: None,
+    opis: "ustawia przeszkolenie biegłe.",
+};
+
+pub const ZNAKOMITA: Opcja = Opcja {
+    krótkie: &['z'],
+    długie: &["znakomita"],
+    tolerancja: TOLERANCJA_WARTOŚCI,
+    parametr: None,
+    opis: "ustawia jakość znakomitą narzędzia.",
+};
+
+pub const PRZYZWOITA: Opcja = Opcja {
+    krótkie: &['p'],
+    długie: &["przyzwoita"],
+    tolerancja: TOLERANCJA_WARTOŚCI,
+    parametr: None,
+    opis: "ustawia jakość przyzwoitą narzędzia.",
+};
+
+pub const KIEPSKA: Opcja = Opcja {
+    krótkie: &['k'],
+    długie: &["kiepska"],
+    tolerancja: TOLERANCJA_WARTOŚCI,
+    parametr: None,
+    opis: "ustawia jakość lichą narzędzia.",
+};
+
+pub const PLUS: Opcja = Opcja {
+    krótkie: &['p'],
+    długie: &["plus"],
+    tolerancja: TOLERANCJA,
+    parametr: Some("N"),
+    opis: "zwiększa liczbę kości wzorca przy rzucie o wskazaną liczbę N.",
+};
+
+pub const MINUS: Opcja = Opcja {
+    krótkie: &['m'],
+    długie: &["minus"],
+    tolerancja: TOLERANCJA,
+    parametr: Some("N"),
+    opis: "zmniejsza liczbę kości wzorca przy rzucie o wskazaną liczbę N.",
+};
+
+pub const PREMIA: Opcja = Opcja {
+    krótkie: &[],
+    długie: &["premia"],
+    tolerancja: TOLERANCJA,
+    parametr: Some("N"),
+    opis: "zwiększa liczbę kości wzorca przy próbie o wskazaną liczbę N.",
+};
+
+pub const KARA: Opcja = Opcja {
+    krótkie: &[],
+    długie: &["kara"],
+    tolerancja: TOLERANCJA,
+    parametr: Some("N"),
+    opis: "zmniejsza liczbę kości wzorca przy próbie o wskazaną liczbę N.",
+};
+
+pub const WSZYSTKIE: Opcja = Opcja {
+    krótkie: &[],
+    długie: &["wszystkie"],
+    tolerancja: TOLERANCJA,
+    parametr: None,
+    opis: "wskazuje wszystkie bronie gracza zamiast podanych z nazwy.",
+};
+
+pub const ZESTAW: Opcja = Opcja {
+    krótkie: &[],
+    długie: &["zestaw"],
+    tolerancja: TOLERANCJA,
+    parametr: Some("STR"),
+    opis: "rzuca bronią z zapisanego zestawu o nazwie STR zamiast broni aktywnych.",
+};
+
+pub const KSIĘŻYCE: Opcja = Opcja {
+    krótkie: &[],
+    długie: &["księżyce"],
+    tolerancja: TOLERANCJA,
+    parametr: None,
+    opis: "przerzuca wszystkie kości, na których wypadł księżyc.",
+};
+
+pub const UKRYTY: Opcja = Opcja {
+    krótkie: &['u'],
+    długie: &["ukryty", "ukryta"],
+    tolerancja: TOLERANCJA,
+    parametr: None,
+    opis: "wysyła wynik rzutu w wiadomości prywatnej, o ile serwer dopuszcza ukryte rzuty.",
+};
+
+/* # komendy */
+
+pub struct Komenda {
+    pub nazwa: &'static str,
+    pub opis: &'static str,
+    pub opcje: &'static [Opcja],
+    pub podkomendy: &'static [Komenda],
+    pub przykłady: &'static [(&'static str, &'static str)],
+}
+
+impl Komenda {
+    pub fn znajdź(nazwa: &str) -> Option<&'static Self> {
+        KOMENDY
+            .iter()
+            .map(|komenda| (dist(komenda.nazwa, nazwa), komenda))
+            .filter(|(odległość, _)| *odległość < TOLERANCJA)
+            .min_by_key(|(odległość, _)| *odległość)
+            .map(|(_, komenda)| komenda)
+    }
+
+    /// linie pomocy komendy. tłumaczenia opisów szukane są po ścieżce komendy, np. `broń.dodaj`.
+    fn linie(&self, język: Język, ścieżka: &str, wcięcie: usize) -> Vec<String> {
+        let odstęp = " ".repeat(wcięcie);
+        let opis = teksty::przetłumacz(język, &format!("komendy.{ścieżka}"), self.opis);
+        let mut linie = vec![format!("{odstęp}{} : {opis}", self.nazwa)];
+        linie.extend(self.opcje.iter().map(|opcja| {
+            let opis = teksty::przetłumacz(język, &format!("opcje.{}", opcja.wartość()), opcja.opis);
+            format!("{odstęp}  {} : {opis}", opcja.nagłówek())
+        }));
+        for podkomenda in self.podkomendy {
+            linie.extend(podkomenda.linie(
+                język,
+                &format!("{ścieżka}.{}", podkomenda.nazwa),
+                wcięcie + 2,
+            ));
+        }
+        linie
+    }
+
+    /// szczegółowe wsparcie komendy, z przykładami poprzedzonymi prefiksem gildii.
+    pub fn pomoc(&self, język: Język, prefiks: &str) -> String {
+        let mut linie = self.linie(język, self.nazwa, 0);
+        if linie.len() > 1 {
+            let wstęp = teksty::tekst(język, "pomoc.argumenty");
+            linie[0] = format!("{} {wstęp}", linie[0]);
+        }
+        if !self.przykłady.is_empty() {
+            linie.push(String::new());
+            linie.push(teksty::tekst(język, "pomoc.przykładowo"));
+            linie.extend(self.przykłady.iter().enumerate().map(|(i, (przykład, opis))| {
+                let klucz = format!("przykłady.{}.{i}", self.nazwa);
+                format!("{prefiks} {przykład} : {}", teksty::przetłumacz(język, &klucz, opis))
+            }));
+        }
+        linie.join("\n")
+    }
+}
+
+pub const KOMENDY: &[Komenda] = &[
+    Komenda {
+        nazwa: "kurwa",
+        opis: "wyświetla wsparcie. użyta z nazwą komendy jako argumentem wyświetla szczegółowe wsparcie tej komendy.",
+        opcje: &[],
+        podkomendy: &[],
+        przykłady: &[("kurwa broń", "wyświetla wsparcie komendy broń.")],
+    },
+    Komenda {
+        nazwa: "wzorzec",
+        opis: "użyta bez argumentu wyświetla wzorzec wybranego gracza, lub informuje o jego braku. użyta z argumentem ustawia wzorzec gracza na wybrany, również jeden z wzorców serwera, np. `--olbrzym`.",
+        opcje: &[CHOJRAK, SZELMA],
+        podkomendy: &[],
+        przykłady: &[("wzorzec --chojraczka", "ustawia użytkowniczce wzorzec chojraczki.")],
+    },
+    Komenda {
+        nazwa: "broń",
+        opis: "użyta bez argumentu wyświetla bronie wybranego gracza. nazwy broni gracza są unikalne, a gdy podana nazwa przypomina kilka z nich, bot wymienia je i prosi o dokładną.",
+        opcje: &[],
+        podkomendy: &[
+            Komenda {
+                nazwa: "dodaj",
+                opis: "dodaje nową broń gracza (jako niewybraną). konieczne są opcje nazwy, wagi oraz zasięgu, a cechy są opcjonalne.",
+                opcje: &[NAZWA, WAGA, ZASIĘG, CECHY],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "wybierz",
+                opis: "wybiera wskazane bronie jako aktywne. opcja nazwy przyjmuje kilka nazw oddzielonych spacjami, a bronie aktywne muszą mieścić się w uzbrojeniu dozwolonym przez reguły (domyślnie dwie ręce i jedna broń ciężka).",
+                opcje: &[NAZWA, WSZYSTKIE],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "schowaj",
+                opis: "wybiera wskazane bronie jako nieaktywne. opcja nazwy przyjmuje kilka nazw oddzielonych spacjami.",
+                opcje: &[NAZWA, WSZYSTKIE],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "oddaj",
+                opis: "oddaje broń o nazwie podanej jako argument STR lub opcją graczowi podanemu wzmianką. broń wraz z cechami przechodzi do niego dopiero, gdy przyjmie ją przyciskiem.",
+                opcje: &[NAZWA],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "zestaw",
+                opis: "zapisuje wskazane bronie jako zestaw o nazwie podanej jako argument STR, używany przez `bitwa --zestaw`. bez broni usuwa zestaw, a bez nazwy wyświetla zestawy gracza.",
+                opcje: &[NAZWA, WSZYSTKIE],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "zmień",
+                opis: "zmienia wagę lub zasięg wskazanej broni, zachowując jej pozostałe wartości. broń aktywna musi po zmianie mieścić się w uzbrojeniu.",
+                opcje: &[NAZWA, WAGA, ZASIĘG],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "przemianuj",
+                opis: "nadaje wskazanej broni nową nazwę, podaną opcją nowej nazwy.",
+                opcje: &[NAZWA, NOWA],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "usuń",
+                opis: "usuwa wskazaną broń z wyposażenia gracza.",
+                opcje: &[NAZWA],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "weź",
+                opis: "bierze kopię szablonu broni o podanej nazwie z arsenału serwera. opcja nazwy zmienia nazwę kopii.",
+                opcje: &[NAZWA],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+        ],
+        przykłady: &[
+            (
+                "broń dodaj -n rozkurwiator -wC --zasięg B",
+                "ustawia użytkownikowi białą broń ciężką o nazwie 'rozkurwiator'.",
+            ),
+            (
+                "broń wybierz -n rozkurwiator procarka",
+                "wybiera jako aktywne bronie 'rozkurwiator' oraz 'procarka'.",
+            ),
+        ],
+    },
+    Komenda {
+        nazwa: "osłona",
+        opis: "użyta bez argumentu wyświetla pancerze i tarcze wybranego gracza. założone osłony zatrzymują w bitwie skazy według reguł.",
+        opcje: &[],
+        podkomendy: &[
+            Komenda {
+                nazwa: "dodaj",
+                opis: "dodaje nową osłonę gracza (jako zdjętą). konieczne są trwałość jako pierwszy argument oraz opcje nazwy i rodzaju, a jakość jest domyślnie przyzwoita.",
+                opcje: &[NAZWA, RODZAJ, ZNAKOMITA, PRZYZWOITA, KIEPSKA],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "załóż",
+                opis: "zakłada wskazaną osłonę.",
+                opcje: &[NAZWA],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "zdejmij",
+                opis: "zdejmuje wskazaną osłonę.",
+                opcje: &[NAZWA],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "usuń",
+                opis: "usuwa wskazaną osłonę z wyposażenia gracza.",
+                opcje: &[NAZWA],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "weź",
+                opis: "bierze kopię szablonu osłony o podanej nazwie z arsenału serwera. opcja nazwy zmienia nazwę kopii.",
+                opcje: &[NAZWA],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+        ],
+        przykłady: &[(
+            "osłona dodaj 3 -n kolczuga -rP -z",
+            "dodaje użytkownikowi znakomity pancerz o nazwie 'kolczuga' i trwałości trzy.",
+        )],
+    },
+    Komenda {
+        nazwa: "arsenał",
+        opis: "szablony broni i osłon wspólne dla serwera. użyta bez argumentu wyświetla arsenał, zmieniać go może tylko mistrz gry, a dopóki serwer nie ustawi roli mistrza gry, administrator.",
+        opcje: &[],
+        podkomendy: &[
+            Komenda {
+                nazwa: "broń",
+                opis: "zapisuje szablon broni o podanej nazwie, wadze, zasięgu oraz cechach, jak `broń dodaj`.",
+                opcje: &[NAZWA, WAGA, ZASIĘG, CECHY],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "osłona",
+                opis: "zapisuje szablon osłony o podanej trwałości, nazwie, rodzaju oraz jakości, jak `osłona dodaj`.",
+                opcje: &[NAZWA, RODZAJ, ZNAKOMITA, PRZYZWOITA, KIEPSKA],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "usuń",
+                opis: "usuwa szablon o podanej nazwie z arsenału. kopie wzięte przez graczy pozostają bez zmian.",
+                opcje: &[NAZWA],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+        ],
+        przykłady: &[
+            (
+                "arsenał broń -n miecz -wŚ -zB -cD",
+                "zapisuje szablon średniej białej broni dwuręcznej o nazwie 'miecz'.",
+            ),
+            (
+                "broń weź miecz -n szczerbiec",
+                "gracz bierze kopię szablonu 'miecz' o nazwie 'szczerbiec'.",
+            ),
+        ],
+    },
+    Komenda {
+        nazwa: "próba",
+        opis: "wykonuje rzut kośćmi jak przy próbie, umożliwia wybranie stopnia przeszkolenia, premii lub kary oraz narzędzi, z których każde dokłada swoją kość. aby wykonać próbę, gracz musi mieć ustawiony wzorzec. gracze wymienieni wzmianką oraz zgłoszeni komendą pomagam dokładają kości swojego wzorca.",
+        opcje: &[SZKOLONY, BIEGŁY, PREMIA, KARA, ZNAKOMITA, PRZYZWOITA, KIEPSKA, UKRYTY],
+        podkomendy: &[],
+        przykłady: &[
+            (
+                "próba -s -k",
+                "wykonuje rzut z wyszkoleniem podstawowym oraz kiepskim narzędziem.",
+            ),
+            (
+                "próba @kumpel -b -z -k --kara 1",
+                "wykonuje rzut biegły z jedną kością wzorca mniej, znakomitym i kiepskim narzędziem oraz pomocą gracza @kumpel.",
+            ),
+        ],
+    },
+    Komenda {
+        nazwa: "bitwa",
+        opis: "wykonuje rzut kośćmi jak przy bitwie. aby wykonać rzut, gracz musi mieć ustawiony wzorzec. korzysta z wszystkich wybranych broni, albo broni zestawu, oraz założonych osłon gracza. bronie muszą mieścić się w uzbrojeniu dozwolonym przez reguły.",
+        opcje: &[PLUS, MINUS, ZESTAW, UKRYTY],
+        podkomendy: &[],
+        przykłady: &[
+            (
+                "bitwa -m 1",
+                "wykonuje rzut trzema kośćmi wzorca i aktywnymi brońmi gracza.",
+            ),
+            (
+                "bitwa --zestaw dwa_noże",
+                "wykonuje rzut brońmi z zestawu 'dwa_noże' zamiast aktywnych.",
+            ),
+        ],
+    },
+    Komenda {
+        nazwa: "pomagam",
+        opis: "zgłasza pomoc w najbliższej próbie wskazanego wzmianką gracza na tym kanale, o ile wykona ją w ciągu dwóch minut. pomocnik dokłada do próby kości swojego wzorca.",
+        opcje: &[],
+        podkomendy: &[],
+        przykłady: &[(
+            "pomagam @kumpel",
+            "dokłada kości wzorca do najbliższej próby gracza @kumpel.",
+        )],
+    },
+    Komenda {
+        nazwa: "przerzuć",
+        opis: "przerzuca wskazane numerami kości ostatniej próby lub bitwy gracza, numerowane od jedynki w kolejności wyświetlania. pokazuje ścianki przed przerzutem i po nim, a przerzut zapisuje w historii rzutu.",
+        opcje: &[KSIĘŻYCE, UKRYTY],
+        podkomendy: &[],
+        przykłady: &[
+            ("przerzuć 2 5", "przerzuca drugą i piątą kość ostatniego rzutu."),
+            (
+                "przerzuć --księżyce",
+                "przerzuca wszystkie kości ostatniego rzutu, na których wypadł księżyc.",
+            ),
+        ],
+    },
+    Komenda {
+        nazwa: "pula",
+        opis: "wyświetla kości, którymi rzuciłaby próba lub bitwa z tymi samymi argumentami, niczym nie rzucając: kości wzorca po modyfikatorach, kości narzędzi i pomocników oraz kości każdej użytej broni wraz z jej narzędziem.",
+        opcje: &[],
+        podkomendy: &[
+            Komenda {
+                nazwa: "próba",
+                opis: "kości próby.",
+                opcje: &[SZKOLONY, BIEGŁY, PREMIA, KARA, ZNAKOMITA, PRZYZWOITA, KIEPSKA],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "bitwa",
+                opis: "kości bitwy z aktywnymi brońmi albo bronią zestawu.",
+                opcje: &[PLUS, MINUS, ZESTAW],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+        ],
+        przykłady: &[(
+            "pula bitwa -m 1",
+            "wyświetla kości bitwy z jedną kością wzorca mniej.",
+        )],
+    },
+    Komenda {
+        nazwa: "zanik",
+        opis: "wykonuje próbę zaniku, wymaga podania aktualnej wytrzymałości sprzętu jako pierwszego argumentu. umożliwia zmianę jakości sprzętu (domyślnie przyzwoita, jeśli zostanie podana więcej niż jedna, pod uwagę wzięta zostanie tylko ostatnia). z nazwą osłony korzysta z jej trwałości i jakości, a utraconą trwałość zapisuje.",
+        opcje: &[NAZWA, ZNAKOMITA, PRZYZWOITA, KIEPSKA, UKRYTY],
+        podkomendy: &[],
+        przykłady: &[
+            (
+                "zanik 2 -z",
+                "wykonuje rzut zaniku dla znakomitego narzędzia o trwałości dwa.",
+            ),
+            (
+                "zanik -n kolczuga",
+                "wykonuje rzut zaniku osłony 'kolczuga' i zapisuje jej trwałość.",
+            ),
+        ],
+    },
+    Komenda {
+        nazwa: "ustawienia",
+        opis: "dostępna tylko dla administratorów serwera. użyta bez argumentu wyświetla ustawienia serwera.",
+        opcje: &[],
+        podkomendy: &[
+            Komenda {
+                nazwa: "prefiks",
+                opis: "ustawia prefiks komend na serwerze, np. `!k`, najwyżej 16 znaków bez spacji. bez wartości przywraca prefiksy domyślne.",
+                opcje: &[],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "język",
+                opis: "ustawia język odpowiedzi oraz wsparcia spośród (pl, en).",
+                opcje: &[],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "glify",
+                opis: "ustawia znaki słońca i księżyca w wynikach rzutów spośród (unicode, ascii, własne SŁOŃCE KSIĘŻYC), np. emoji serwera.",
+                opcje: &[],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "mg",
+                opis: "ustawia rolę mistrza gry wskazaną wzmianką, lub usuwa ją wartością `brak`.",
+                opcje: &[],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "kanały",
+                opis: "ogranicza rzuty do wskazanych kanałów. przyjmuje (dodaj #kanał, usuń #kanał, wszystkie).",
+                opcje: &[],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "ukryte",
+                opis: "zezwala na ukryte rzuty wartością `tak` lub zabrania ich wartością `nie`.",
+                opcje: &[],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "obrazy",
+                opis: "dołącza do wyników prób i bitew obraz kości wartością `tak` lub wyłącza go wartością `nie`.",
+                opcje: &[],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "reguły",
+                opis: "wybiera zestaw reguł gry spośród podanych w konfiguracji bota. bez wartości przywraca reguły podstawowe.",
+                opcje: &[],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "wzorzec",
+                opis: "dodaje wzorzec serwera o podanej nazwie, sześciu ściankach kości (np. S S SM M X -) oraz aliasach. bez ścianek usuwa wzorzec.",
+                opcje: &[],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "szablon",
+                opis: "zastępuje tekst odpowiedzi o podanym kluczu, np. `zanik.porażka` lub `wynik.treść`. szablon może zawierać {gracz}, {postać} oraz wartości odpowiedzi. bez tekstu przywraca tekst domyślny.",
+                opcje: &[],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "opis",
+                opis: "dodaje losowy opis do wyników o podanym wydźwięku spośród (miażdżący, słońca, remis, księżyce, zniszczenie, trwałość). bez tekstu usuwa opisy wydźwięku.",
+                opcje: &[],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+        ],
+        przykłady: &[
+            ("ustawienia kanały dodaj #sesja", "dopuszcza rzuty na kanale #sesja."),
+            ("ustawienia mg @prowadzący", "ustawia rolę mistrza gry."),
+            (
+                "ustawienia opis zniszczenie {postać} z żalem patrzy na resztki sprzętu.",
+                "dodaje opis do zniszczenia narzędzia.",
+            ),
+        ],
+    },
+    Komenda {
+        nazwa: "preferencje",
+        opis: "ustawienia gracza, obowiązujące na każdym serwerze. użyta bez argumentu wyświetla preferencje gracza.",
+        opcje: &[],
+        podkomendy: &[
+            Komenda {
+                nazwa: "opisowe",
+                opis: "wartością `tak` opisuje wyniki rzutów słowami, czytelnymi dla czytników ekranu, a wartością `nie` przywraca glify.",
+                opcje: &[],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+            Komenda {
+                nazwa: "język",
+                opis: "ustawia język odpowiedzi gracza spośród (pl, en), a wartością `serwer` przywraca język serwera.",
+                opcje: &[],
+                podkomendy: &[],
+                przykłady: &[],
+            },
+        ],
+        przykłady: &[
+            (
+                "preferencje opisowe tak",
+                "wyświetla wyniki jako np. '3 słońca, 2 księżyce, 1 skaza z narzędzia'.",
+            ),
+            ("preferencje język en", "odpowiada graczowi po angielsku."),
+        ],
+    },
+];
+
+pub fn przegląd(język: Język) -> String {
+    let mut linie = vec![teksty::tekst(język, "pomoc.wstęp"), "```".to_owned()];
+    linie.extend(KOMENDY.iter().map(|komenda| {
+        let opis = teksty::przetłumacz(język, &format!("komendy.{}", komenda.nazwa), komenda.opis);
+        format!("{} : {opis}", komenda.nazwa)
+    }));
+    linie.push("```".to_owned());
+    linie.push(teksty::tekst(język, "pomoc.szczegóły"));
+    linie.join("\n")
+}
+
+/// pierwsze zdanie opisu, skrócone do limitu opisów poleceń discorda.
+pub fn skrót(opis: &str) -> String {
+    let zdanie = opis.split_inclusive(". ").next().unwrap_or(opis).trim_end();
+    potnij(zdanie, LIMIT_OPISU).swap_remove(0)
+}
+
+/* # dzielenie długich wiadomości */
+
+fn potnij(linia: &str, limit: usize) -> Vec<String> {
+    let znaki = linia.chars().collect::<Vec<char>>();
+    if znaki.is_empty() {
+        return vec![String::new()];
+    }
+    znaki
+        .chunks(limit)
+        .map(|kawałek| kawałek.iter().collect())
+        .collect()
+}
+
+pub fn podziel(tekst: &str, limit: usize) -> Vec<String> {
+    let mut części = Vec::new();
+    let mut bieżąca = String::new();
+    for linia in tekst.lines().flat_map(|linia| potnij(linia, limit)) {
+        if !bieżąca.is_empty() && bieżąca.chars().count() + linia.chars().count() + 1 > limit {
+            części.push(std::mem::take(&mut bieżąca));
+        }
+        if !bieżąca.is_empty() {
+            bieżąca.push('\n');
+        }
+        bieżąca.push_str(&linia);
+    }
+    if !bieżąca.is_empty() {
+        części.push(bieżąca);
+    }
+    części
+}
+
+/// dzieli tekst na bloki kodu mieszczące się w pojedynczej wiadomości.
+pub fn podziel_blok(tekst: &str) -> Vec<String> {
+    podziel(tekst, LIMIT_WIADOMOŚCI - 8)
+        .into_iter()
+        .map(|część| format!("```\n{część}\n```"))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn podział() {
+        assert_eq!(podziel("abc\ndef\nghi", 7), vec!["abc\ndef", "ghi"]);
+        assert_eq!(podziel("abcdefgh", 3), vec!["abc", "def", "gh"]);
+        assert!(podziel_blok(&"x\n".repeat(3000))
+            .iter()
+            .all(|część| część.chars().count() <= LIMIT_WIADOMOŚCI));
+        assert!(Komenda::znajdź("bron").is_some());
+        assert!(Komenda::znajdź("kobyła").is_none());
+        assert_eq!(skrót("pierwsze zdanie. drugie zdanie."), "pierwsze zdanie.");
+        assert!(KOMENDY
+            .iter()
+            .all(|komenda| skrót(komenda.opis).chars().count() <= LIMIT_OPISU));
+    }
+}
@@ -0,0 +1,308 @@
+use crate::{dziennik::UstawieniaDziennika, magazyn::Magazyn, reguły::UstawieniaReguł};
+use clap::Parser;
+use serde::Deserialize;
+use serenity::model::prelude::{ChannelId, GuildId};
+use std::{
+    collections::HashMap,
+    env,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/* konfiguracja bota wczytywana z pliku toml i nadpisywana argumentami wiersza poleceń */
+
+const DOMYŚLNA_ŚCIEŻKA: &str = "kobler.toml";
+
+/* # token */
+
+#[derive(Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+pub enum ŹródłoTokenu {
+    Plik { plik: PathBuf },
+    Zmienna { zmienna: String },
+}
+
+impl Default for ŹródłoTokenu {
+    fn default() -> Self {
+        Self::Plik {
+            plik: PathBuf::from("auth-token.secret"),
+        }
+    }
+}
+
+impl ŹródłoTokenu {
+    pub fn odczytaj(&self) -> Result<String, Box<dyn Error>> {
+        match self {
+            Self::Plik { plik } => fs::read_to_string(plik)
+                .map_err(|why| format!("nie udało się odczytać tokenu z {}: {why}", plik.display()).into()),
+            Self::Zmienna { zmienna } => env::var(zmienna)
+                .map_err(|why| format!("nie udało się odczytać tokenu ze zmiennej {zmienna}: {why}").into()),
+        }
+    }
+}
+
+/* # prefiksy */
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Prefiksy {
+    pub znak: String,
+    pub grupy: Vec<String>,
+}
+
+impl Default for Prefiksy {
+    fn default() -> Self {
+        Self {
+            znak: ":".to_owned(),
+            grupy: vec!["kobler".to_owned(), "k".to_owned()],
+        }
+    }
+}
+
+impl Prefiksy {
+    /// pełne prefiksy, od najdłuższego, by `:k` nie przesłaniał `:kobler`.
+    pub fn pełne(&self) -> Vec<String> {
+        let mut pełne = self
+            .grupy
+            .iter()
+            .map(|grupa| format!("{}{grupa}", self.znak))
+            .collect::<Vec<_>>();
+        pełne.sort_by_key(|prefiks| std::cmp::Reverse(prefiks.chars().count()));
+        pełne
+    }
+}
+
+/* # powitanie */
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Powitanie {
+    pub aktywne: bool,
+    pub gildie: HashMap<String, u64>,
+    pub pomijane: Vec<u64>,
+}
+
+impl Default for Powitanie {
+    fn default() -> Self {
+        Self {
+            aktywne: true,
+            gildie: HashMap::new(),
+            pomijane: Vec::new(),
+        }
+    }
+}
+
+pub enum KanałPowitania {
+    Wybrany(ChannelId),
+    Domyślny,
+    Brak,
+}
+
+impl Powitanie {
+    pub fn kanał(&self, gildia: GuildId) -> KanałPowitania {
+        if !self.aktywne || self.pomijane.contains(&gildia.0) {
+            KanałPowitania::Brak
+        } else {
+            self.gildie
+                .get(&gildia.0.to_string())
+                .map_or(KanałPowitania::Domyślny, |kanał| {
+                    KanałPowitania::Wybrany(ChannelId(*kanał))
+                })
+        }
+    }
+}
+
+/* # całość */
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Konfiguracja {
+    pub token: ŹródłoTokenu,
+    pub prefiksy: Prefiksy,
+    pub powitanie: Powitanie,
+    pub magazyn: Magazyn,
+    pub dziennik: UstawieniaDziennika,
+    pub reguły: UstawieniaReguł,
+}
+
+#[derive(Parser)]
+#[clap(about = "bot do koblowania zgodnego ze Zdrżeniem Urojonych Gier.")]
+struct Argumenty {
+    /// ścieżka pliku konfiguracji.
+    #[clap(short, long, value_name = "PLIK")]
+    konfiguracja: Option<PathBuf>,
+    /// plik, z którego odczytany zostanie token.
+    #[clap(long, value_name = "PLIK", conflicts_with = "token-zmienna")]
+    token_plik: Option<PathBuf>,
+    /// zmienna środowiskowa, z której odczytany zostanie token.
+    #[clap(long, value_name = "ZMIENNA")]
+    token_zmienna: Option<String>,
+    /// znak rozpoczynający komendy.
+    #[clap(long, value_name = "ZNAK")]
+    prefiks: Option<String>,
+    /// nazwa grupy komend, można podać wielokrotnie.
+    #[clap(long, value_name = "NAZWA")]
+    grupa: Vec<String>,
+    /// wyłącza powitanie po uruchomieniu.
+    #[clap(long)]
+    bez_powitania: bool,
+    /// rodzaj magazynu: pamięć lub plik.
+    #[clap(long, value_name = "RODZAJ")]
+    magazyn: Option<String>,
+    /// katalog magazynu plikowego.
+    #[clap(long, value_name = "KATALOG")]
+    katalog: Option<PathBuf>,
+    /// poziom dziennika, w składni filtrów tracing.
+    #[clap(long, value_name = "POZIOM")]
+    poziom: Option<String>,
+    /// plik dziennika, domyślnie wypisywany jest na konsolę.
+    #[clap(long, value_name = "PLIK")]
+    dziennik: Option<PathBuf>,
+    /// plik dziennika audytu, pusta ścieżka go wyłącza.
+    #[clap(long, value_name = "PLIK")]
+    audyt: Option<PathBuf>,
+    /// plik reguł gry obowiązujących domyślnie.
+    #[clap(long, value_name = "PLIK")]
+    reguły: Option<PathBuf>,
+}
+
+impl Konfiguracja {
+    pub fn z_tekstu(tekst: &str) -> Result<Self, Box<dyn Error>> {
+        let konfiguracja: Self = toml::from_str(tekst)?;
+        konfiguracja.sprawdź()?;
+        Ok(konfiguracja)
+    }
+
+    fn z_pliku(ścieżka: &Path) -> Result<Self, Box<dyn Error>> {
+        let tekst = fs::read_to_string(ścieżka).map_err(|why| {
+            format!("nie udało się odczytać konfiguracji {}: {why}", ścieżka.display())
+        })?;
+        Self::z_tekstu(&tekst)
+            .map_err(|why| format!("niepoprawna konfiguracja {}: {why}", ścieżka.display()).into())
+    }
+
+    fn sprawdź(&self) -> Result<(), Box<dyn Error>> {
+        if self.prefiksy.znak.is_empty() && self.prefiksy.grupy.iter().any(String::is_empty) {
+            return Err("prefiks komend nie może być pusty".into());
+        }
+        if self.prefiksy.grupy.is_empty() {
+            return Err("należy podać co najmniej jedną grupę komend".into());
+        }
+        if let Some(gildia) = self.powitanie.gildie.keys().find(|gildia| gildia.parse::<u64>().is_err()) {
+            return Err(format!("niepoprawny identyfikator gildii `{gildia}`").into());
+        }
+        if let Some(nazwa) = self.reguły.zestawy.keys().find(|nazwa| nazwa.contains(char::is_whitespace)) {
+            return Err(format!("nazwa zestawu reguł `{nazwa}` nie może zawierać odstępów").into());
+        }
+        Ok(())
+    }
+
+    fn nadpisz(&mut self, argumenty: Argumenty) -> Result<(), Box<dyn Error>> {
+        if let Some(plik) = argumenty.token_plik {
+            self.token = ŹródłoTokenu::Plik { plik };
+        }
+        if let Some(zmienna) = argumenty.token_zmienna {
+            self.token = ŹródłoTokenu::Zmienna { zmienna };
+        }
+        if let Some(znak) = argumenty.prefiks {
+            self.prefiksy.znak = znak;
+        }
+        if !argumenty.grupa.is_empty() {
+            self.prefiksy.grupy = argumenty.grupa;
+        }
+        if argumenty.bez_powitania {
+            self.powitanie.aktywne = false;
+        }
+        match (argumenty.magazyn.as_deref(), argumenty.katalog) {
+            (Some("pamięć"), None) => self.magazyn = Magazyn::Pamięć,
+            (Some("plik") | None, Some(katalog)) => self.magazyn = Magazyn::Plik { katalog },
+            (Some("plik"), None) => {
+                if let Magazyn::Pamięć = self.magazyn {
+                    return Err("magazyn plikowy wymaga podania katalogu".into());
+                }
+            }
+            (None, None) => {}
+            (Some(rodzaj), _) => return Err(format!("nieznany rodzaj magazynu `{rodzaj}`").into()),
+        }
+        if let Some(poziom) = argumenty.poziom {
+            self.dziennik.poziom = poziom;
+        }
+        if let Some(plik) = argumenty.dziennik {
+            self.dziennik.plik = Some(plik);
+        }
+        if let Some(audyt) = argumenty.audyt {
+            self.dziennik.audyt = Some(audyt);
+        }
+        if let Some(plik) = argumenty.reguły {
+            self.reguły.plik = Some(plik);
+        }
+        self.sprawdź()
+    }
+
+    /// wczytuje konfigurację z pliku wskazanego w argumentach lub z `kobler.toml`, o ile istnieje.
+    pub fn wczytaj() -> Result<Self, Box<dyn Error>> {
+        let argumenty = Argumenty::parse();
+        let mut konfiguracja = match &argumenty.konfiguracja {
+            Some(ścieżka) => Self::z_pliku(ścieżka)?,
+            None if Path::new(DOMYŚLNA_ŚCIEŻKA).exists() => {
+                Self::z_pliku(Path::new(DOMYŚLNA_ŚCIEŻKA))?
+            }
+            None => Self::default(),
+        };
+        konfiguracja.nadpisz(argumenty)?;
+        Ok(konfiguracja)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn konfiguracja() {
+        let konfiguracja = Konfiguracja::z_tekstu(
+            r#"
+            [token]
+            zmienna = "KOBLER_TOKEN"
+
+            [prefiksy]
+            znak = "!"
+            grupy = ["k", "kobler"]
+
+            [powitanie]
+            pomijane = [2]
+
+            [powitanie.gildie]
+            "1" = 10
+
+            [magazyn]
+            rodzaj = "plik"
+            katalog = "dane"
+
+            [dziennik]
+            poziom = "debug"
+
+            ["reguły".zestawy]
+            domowe = "reguły-domowe.toml"
+            "#,
+        )
+        .unwrap();
+        assert!(matches!(konfiguracja.token, ŹródłoTokenu::Zmienna { .. }));
+        assert_eq!(konfiguracja.prefiksy.pełne(), vec!["!kobler", "!k"]);
+        assert!(matches!(
+            konfiguracja.powitanie.kanał(GuildId(1)),
+            KanałPowitania::Wybrany(ChannelId(10))
+        ));
+        assert!(matches!(konfiguracja.powitanie.kanał(GuildId(2)), KanałPowitania::Brak));
+        assert!(matches!(konfiguracja.powitanie.kanał(GuildId(3)), KanałPowitania::Domyślny));
+        assert!(matches!(konfiguracja.magazyn, Magazyn::Plik { .. }));
+        assert_eq!(konfiguracja.dziennik.poziom, "debug");
+        assert!(konfiguracja.reguły.plik.is_none());
+        assert!(konfiguracja.reguły.zestawy.contains_key("domowe"));
+
+        assert!(Konfiguracja::z_tekstu("").is_ok());
+        assert!(Konfiguracja::z_tekstu("[powitanie.gildie]\nabc = 1").is_err());
+        assert!(Konfiguracja::z_tekstu("[magazyn]\nrodzaj = \"chmura\"").is_err());
+    }
+}
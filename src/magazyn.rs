@@ -0,0 +1,88 @@
+use crate::error::Błąd;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serenity::prelude::{TypeMap, TypeMapKey};
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/* trwałe przechowywanie stanu bota */
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(tag = "rodzaj", rename_all = "lowercase")]
+pub enum Magazyn {
+    #[default]
+    Pamięć,
+    Plik { katalog: PathBuf },
+}
+
+/// holder w TypeMap, którego zawartość zapisywana jest w magazynie pod własną nazwą.
+pub trait Tabela: TypeMapKey {
+    const NAZWA: &'static str;
+}
+
+pub struct MagazynHolder;
+
+impl TypeMapKey for MagazynHolder {
+    type Value = Magazyn;
+}
+
+fn błąd(ścieżka: &Path, why: impl fmt::Display) -> Błąd {
+    Błąd::Magazyn(format!("{}: {why}", ścieżka.display()))
+}
+
+impl Magazyn {
+    fn ścieżka(katalog: &Path, tabela: &str) -> PathBuf {
+        katalog.join(format!("{tabela}.json"))
+    }
+
+    pub fn wczytaj<T>(&self, tabela: &str) -> Result<T, Błąd>
+    where
+        T: DeserializeOwned + Default,
+    {
+        match self {
+            Self::Pamięć => Ok(T::default()),
+            Self::Plik { katalog } => {
+                let ścieżka = Self::ścieżka(katalog, tabela);
+                match fs::read_to_string(&ścieżka) {
+                    Ok(treść) => serde_json::from_str(&treść).map_err(|why| błąd(&ścieżka, why)),
+                    Err(why) if why.kind() == io::ErrorKind::NotFound => Ok(T::default()),
+                    Err(why) => Err(błąd(&ścieżka, why)),
+                }
+            }
+        }
+    }
+
+    pub fn zapisz<T>(&self, tabela: &str, dane: &T) -> Result<(), Błąd>
+    where
+        T: Serialize,
+    {
+        match self {
+            Self::Pamięć => Ok(()),
+            Self::Plik { katalog } => {
+                let ścieżka = Self::ścieżka(katalog, tabela);
+                let tymczasowa = ścieżka.with_extension("json.tmp");
+                let treść = serde_json::to_string_pretty(dane).map_err(|why| błąd(&ścieżka, why))?;
+                fs::create_dir_all(katalog).map_err(|why| błąd(katalog, why))?;
+                // zapis przez plik tymczasowy, by przerwany zapis nie uszkodził danych
+                fs::write(&tymczasowa, treść).map_err(|why| błąd(&tymczasowa, why))?;
+                fs::rename(&tymczasowa, &ścieżka).map_err(|why| błąd(&ścieżka, why))
+            }
+        }
+    }
+}
+
+/// zapisuje zawartość wskazanej tabeli z TypeMap w magazynie.
+pub fn zachowaj<T>(data: &TypeMap) -> Result<(), Błąd>
+where
+    T: Tabela,
+    T::Value: Serialize,
+{
+    let magazyn = data
+        .get::<MagazynHolder>()
+        .ok_or_else(|| Błąd::Magazyn("brak MagazynHolder w TypeMap".to_owned()))?;
+    let dane = data
+        .get::<T>()
+        .ok_or_else(|| Błąd::Magazyn(format!("brak tabeli {} w TypeMap", T::NAZWA)))?;
+    magazyn.zapisz(T::NAZWA, dane)
+}
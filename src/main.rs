@@ -3,69 +3,341 @@
 #![feature(extract_if)]
 #![feature(hash_extract_if)]
 
-use crate::parser::Arg;
+use crate::{
+    error::Błąd,
+    komendy::Opcja,
+    konfiguracja::{KanałPowitania, Konfiguracja, Powitanie},
+    magazyn::{zachowaj, MagazynHolder, Tabela},
+    parser::{Arg, InvalidArgument},
+    reguły::{Kość, Reguły, RegułyHolder, WłasnyWzorzec, Zestawy, ŚCIANKI_WZORCA},
+    teksty::Mowa,
+    ustawienia::{Glify, Język, UstawieniaGraczaHolder, UstawieniaHolder},
+    wyniki::Odpowiedź,
+};
 use derivative::Derivative;
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 use serenity::{
     async_trait,
     client::{Client, Context, EventHandler},
+    http::Http,
     framework::standard::{
         macros::{command, group, hook},
-        Args, CommandResult, StandardFramework,
+        Args, CommandResult, DispatchError, StandardFramework,
+    },
+    model::{
+        channel::Message,
+        gateway::Ready,
+        guild::Guild,
+        interactions::Interaction,
+        misc::Mentionable,
+        prelude::{ChannelId, GuildId, MessageId, RoleId, UserId},
     },
-    model::{channel::Message, gateway::Ready, prelude::UserId},
-    prelude::TypeMapKey,
+    prelude::{TypeMap, TypeMapKey},
+    utils::{parse_channel, parse_role, parse_username},
 };
 use std::{
-    collections::{HashMap, HashSet},
-    fmt, fs,
-    sync::Arc,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 use strsim::damerau_levenshtein as dist;
 
 /* constants and modules */
 
+mod dziennik;
+mod error;
+mod interakcje;
+mod komendy;
+mod konfiguracja;
+mod magazyn;
+mod obrazy;
 mod parser;
+mod reguły;
+mod teksty;
+mod ustawienia;
+mod wyniki;
 mod zug;
-pub const KRZYCZ: &str = "krzycz `:kobler kurwa` by otrzymać wsparcie.";
+const RZUTY: &[&str] = &["próba", "bitwa", "zanik", "przerzuć"];
+const OKNO_POMOCY: Duration = Duration::from_secs(120); // czas na próbę po zgłoszeniu pomocy
+const LIMIT_PREFIKSU: usize = 16;
 
 /* helper functions */
 
+fn argumenty(args: Args) -> Result<Vec<Arg>, Błąd> {
+    Ok(Arg::try_parse(args)?)
+}
+
+/// wszystkie nazwy podane w opcjach nazwy. polecenia discorda podają je w jednej wartości.
+fn nazwy(args: &[Arg]) -> Result<Vec<&str>, Błąd> {
+    let nazwy = args
+        .iter()
+        .filter_map(|arg| komendy::NAZWA.dopasuj(arg))
+        .flatten()
+        .flat_map(|param| param.split_whitespace())
+        .collect::<Vec<_>>();
+    if nazwy.is_empty() {
+        return Err(Błąd::BrakArgumentu("nazwa"));
+    }
+    Ok(nazwy)
+}
+
+/// modyfikator liczby kości wzorca z opcji zwiększającej i zmniejszającej. parametry, które
+/// nie są liczbą, np. wzmianki w `--kara 1 @gracz`, są pomijane.
+fn modyfikator(args: &[Arg], plus: &Opcja, minus: &Opcja) -> isize {
+    let liczba = |options: &[String]| {
+        options
+            .iter()
+            .rev()
+            .find_map(|option| option.parse::<isize>().ok())
+    };
+    // zbyt duże modyfikatory odrzuca dopiero limit kości puli, więc nie mogą się przepełnić
+    let suma = |opcja: &Opcja| {
+        args.iter()
+            .filter_map(|arg| opcja.dopasuj(arg))
+            .filter_map(liczba)
+            .fold(0, isize::saturating_add)
+    };
+    suma(plus).saturating_sub(suma(minus))
+}
+
+/// gracze wymienieni wzmianką, również jako parametr opcji, np. `próba -s @gracz`.
+fn wzmianki(args: &[Arg]) -> Vec<UserId> {
+    args.iter()
+        .flat_map(|arg| match arg {
+            Arg::Plain(tekst) => std::slice::from_ref(tekst),
+            Arg::Short(_, params) | Arg::Long(_, params) => params.as_slice(),
+        })
+        .filter_map(parse_username)
+        .map(UserId)
+        .unique()
+        .collect()
+}
+
+fn nazwa(args: &[Arg]) -> Result<&str, Błąd> {
+    args.iter()
+        .filter_map(|arg| komendy::NAZWA.dopasuj(arg))
+        .next_back()
+        .and_then(<[String]>::last)
+        .map(String::as_str)
+        .ok_or(Błąd::BrakArgumentu("nazwa"))
+}
+
+/// czy rzut ma być ukryty, o ile gildia na to pozwala.
+async fn ukryty(ctx: &Context, nadawca: &Nadawca, args: &[Arg]) -> Result<bool, Błąd> {
+    if !args.iter().any(|arg| komendy::UKRYTY.pasuje(arg)) {
+        return Ok(false);
+    }
+    if nadawca.gildia.is_some()
+        && !ustawienia::gildii(ctx, nadawca).await.ukryte_rzuty
+        && !ustawienia::czy_mg(ctx, nadawca).await
+    {
+        return Err(Błąd::Niedozwolone("ukryte"));
+    }
+    Ok(true)
+}
+
+/// wysyła wynik rzutu, prywatnie jeśli rzut jest ukryty. jawny wynik można ponowić przyciskiem.
+/// o ukryciu trzeba rozstrzygnąć przed rzutem, aby odrzucony ukryty rzut niczego nie zmienił.
+async fn ogłoś_wynik(
+    ctx: &Context,
+    msg: &Message,
+    komenda: &str,
+    args: &[Arg],
+    ukryty: bool,
+    wynik: Odpowiedź,
+) -> CommandResult {
+    let mowa = ustawienia::mowa(ctx, &Nadawca::from(msg)).await;
+    if ukryty {
+        msg.author.dm(ctx, |m| wynik.wiadomość(m)).await?;
+        msg.reply(ctx, mowa.tekst("rzut.ukryty")).await?;
+    } else {
+        msg.channel_id
+            .send_message(ctx, |m| {
+                wynik
+                    .wiadomość(m)
+                    .reference_message(msg)
+                    .allowed_mentions(|a| a.replied_user(false))
+                    .components(|c| interakcje::składniki(c, &mowa, msg.author.id, komenda, args))
+            })
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// prefiksy komend w gildii nadawcy: własny prefiks gildii albo prefiksy z konfiguracji.
+async fn prefiksy(ctx: &Context, nadawca: &Nadawca) -> Vec<String> {
+    let własny = ustawienia::gildii(ctx, nadawca).await.prefiks;
+    let data = ctx.data.read().await;
+    własny.map_or_else(
+        || data.get::<PrefiksyHolder>().cloned().unwrap_or_default(),
+        |prefiks| vec![prefiks],
+    )
+}
+
+#[hook]
+async fn prefiks(ctx: &Context, msg: &Message) -> Option<String> {
+    prefiksy(ctx, &Nadawca::from(msg))
+        .await
+        .into_iter()
+        .find(|prefiks| msg.content.starts_with(prefiks.as_str()))
+}
+
+#[hook]
+async fn before(ctx: &Context, msg: &Message, command_name: &str) -> bool {
+    let nadawca = Nadawca::from(msg);
+    if RZUTY.contains(&command_name) && !ustawienia::rzuty_dozwolone(ctx, &nadawca).await {
+        let mowa = ustawienia::mowa(ctx, &nadawca).await;
+        if let Err(why) = msg.reply(ctx, Błąd::Niedozwolone("kanał").wiadomość(&mowa)).await {
+            tracing::error!(błąd = ?why, "błąd wiadomości");
+        }
+        return false;
+    }
+
+    let mut data = ctx.data.write().await;
+    if let Some(holder) = data.get_mut::<StartHolder>() {
+        holder.insert(msg.id, Instant::now());
+    }
+    true
+}
+
+#[hook]
+async fn after(ctx: &Context, msg: &Message, command_name: &str, command_result: CommandResult) {
+    let start = ctx
+        .data
+        .write()
+        .await
+        .get_mut::<StartHolder>()
+        .and_then(|holder| holder.remove(&msg.id));
+    let opóźnienie_ms = start.map(|start| start.elapsed().as_millis());
+    let argumenty = dziennik::argumenty(msg, command_name);
+
+    match command_result {
+        Ok(()) => tracing::info!(
+            gildia = ?msg.guild_id,
+            kanał = %msg.channel_id,
+            gracz = %msg.author.id,
+            komenda = command_name,
+            ?argumenty,
+            wynik = "sukces",
+            ?opóźnienie_ms,
+        ),
+        Err(why) => {
+            tracing::warn!(
+                gildia = ?msg.guild_id,
+                kanał = %msg.channel_id,
+                gracz = %msg.author.id,
+                komenda = command_name,
+                ?argumenty,
+                wynik = "błąd",
+                ?opóźnienie_ms,
+                błąd = ?why,
+            );
+            let mowa = ustawienia::mowa(ctx, &Nadawca::from(msg)).await;
+            let wiadomość = why.downcast_ref::<Błąd>().map_or_else(
+                || mowa.tekst("błąd.nieoczekiwany"),
+                |why| why.wiadomość(&mowa),
+            );
+            if let Err(why) = msg.reply(ctx, wiadomość).await {
+                tracing::error!(błąd = ?why, "nie udało się zgłosić błędu komendy");
+            }
+        }
+    }
+}
+
+#[hook]
+async fn dispatch_error(ctx: &Context, msg: &Message, error: DispatchError) {
+    let klucz = match error {
+        DispatchError::LackingPermissions(_) => "błąd.uprawnienia",
+        DispatchError::OnlyForGuilds => "niedozwolone.gildia",
+        error => {
+            tracing::debug!(?error, "odrzucono komendę");
+            return;
+        }
+    };
+    let mowa = ustawienia::mowa(ctx, &Nadawca::from(msg)).await;
+    if let Err(why) = msg.reply(ctx, mowa.tekst(klucz)).await {
+        tracing::error!(błąd = ?why, "błąd wiadomości");
+    }
+}
+
 #[hook]
 async fn unknown_command(ctx: &Context, msg: &Message, unknown_command_name: &str) {
+    let mowa = ustawienia::mowa(ctx, &Nadawca::from(msg)).await;
     let _ = msg
         .channel_id
         .say(
             &ctx.http,
-            &format!(
-                "nie rozpoznano komendy `{}`. {}",
-                unknown_command_name, KRZYCZ
-            ),
+            mowa.wstaw("błąd.nieznana_komenda", &[("komenda", unknown_command_name)]),
         )
         .await;
 }
 
 /* helper types */
 
+/// autor komendy, niezależnie od tego, czy wysłał ją wiadomością, czy poleceniem.
+struct Nadawca {
+    gracz: UserId,
+    gildia: Option<GuildId>,
+    kanał: ChannelId,
+}
+
+impl From<&Message> for Nadawca {
+    fn from(msg: &Message) -> Self {
+        Self {
+            gracz: msg.author.id,
+            gildia: msg.guild_id,
+            kanał: msg.channel_id,
+        }
+    }
+}
+
+struct PrefiksyHolder;
+
+impl TypeMapKey for PrefiksyHolder {
+    type Value = Vec<String>;
+}
+
+struct StartHolder;
+
+impl TypeMapKey for StartHolder {
+    type Value = HashMap<MessageId, Instant>;
+}
+
+/// pomocnicy zgłoszeni komendą `pomagam` według kanału i gracza, któremu pomagają.
+struct PomocHolder;
+
+impl TypeMapKey for PomocHolder {
+    type Value = HashMap<(ChannelId, UserId), Vec<(UserId, Instant)>>;
+}
+
 struct WzorzecHolder;
 
 impl TypeMapKey for WzorzecHolder {
     type Value = HashMap<UserId, zug::Wzorzec>;
 }
 
-#[derive(Derivative)]
-#[derivative(PartialEq, Eq, Hash, PartialOrd, Ord)]
+impl Tabela for WzorzecHolder {
+    const NAZWA: &'static str = "wzorce";
+}
+
+// bronie są tożsame według identyfikatora. wyświetlane są według stanu i nazwy, która jest
+// unikalna wśród broni gracza
+#[derive(Derivative, Serialize, Deserialize)]
+#[derivative(PartialEq, Eq, Hash)]
 struct BrońGracza {
-    #[derivative(
-        PartialEq = "ignore",
-        Hash = "ignore",
-        PartialOrd = "ignore",
-        Ord = "ignore"
-    )]
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
     broń: zug::Broń,
 
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
     aktywna: bool,
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
     nazwa: Arc<str>,
+    /// stały identyfikator, nadawany również broniom zapisanym przed jego wprowadzeniem.
+    #[serde(default = "rand::random")]
+    id: u64,
 }
 
 impl fmt::Display for BrońGracza {
@@ -73,7 +345,7 @@ impl fmt::Display for BrońGracza {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{} ({}) : {}, {})",
+            "{} ({}) : {}, {}",
             self.nazwa,
             match self.aktywna {
                 true => "wybrana",
@@ -81,7 +353,58 @@ impl fmt::Display for BrońGracza {
             },
             self.broń.zasięg_str(),
             self.broń.waga_str()
-        )
+        )?;
+        self.broń
+            .cechy()
+            .try_for_each(|cecha| write!(f, ", {}", cecha.nazwa()))
+    }
+}
+
+/// zasięg, waga oraz cechy broni w języku odpowiedzi.
+fn wartości_broni(broń: &zug::Broń, mowa: &Mowa) -> [(&'static str, String); 3] {
+    let cechy = broń
+        .cechy()
+        .map(|cecha| format!(", {}", mowa.tekst(&format!("cecha.{}", cecha.nazwa()))))
+        .collect();
+    [
+        ("zasięg", mowa.tekst(&format!("zasięg.{}", broń.zasięg_str()))),
+        ("waga", mowa.tekst(&format!("waga.{}", broń.waga_str()))),
+        ("cechy", cechy),
+    ]
+}
+
+/// rodzaj, jakość oraz trwałość osłony w języku odpowiedzi.
+fn wartości_osłony(osłona: &zug::Osłona, mowa: &Mowa) -> [(&'static str, String); 3] {
+    [
+        ("rodzaj", mowa.tekst(&format!("osłona.{}", osłona.rodzaj_str()))),
+        ("jakość", mowa.tekst(&format!("jakość.{}", osłona.jakość.nazwa()))),
+        ("trwałość", osłona.trwałość.to_string()),
+    ]
+}
+
+/// opis sprzętu z jego nazwą, stanem oraz wartościami.
+fn opis_sprzętu(
+    mowa: &Mowa,
+    klucz: &str,
+    nazwa: &str,
+    stan: Option<&str>,
+    wartości: &[(&str, String)],
+) -> String {
+    let stan = stan.map(|stan| mowa.tekst(stan)).unwrap_or_default();
+    let mut pary = vec![("nazwa", nazwa), ("stan", stan.as_str())];
+    pary.extend(wartości.iter().map(|(klucz, wartość)| (*klucz, wartość.as_str())));
+    mowa.wstaw(klucz, &pary)
+}
+
+impl BrońGracza {
+    #[allow(clippy::match_bool)] // i think this is more readable
+    fn opis(&self, mowa: &Mowa) -> String {
+        let stan = match self.aktywna {
+            true => "broń.wybrana",
+            false => "broń.schowana",
+        };
+        let wartości = wartości_broni(&self.broń, mowa);
+        opis_sprzętu(mowa, "broń.opis", &self.nazwa, Some(stan), &wartości)
     }
 }
 
@@ -93,54 +416,289 @@ impl TypeMapKey for BronieGraczaHolder {
     type Value = HashMap<UserId, BronieGracza>;
 }
 
+impl Tabela for BronieGraczaHolder {
+    const NAZWA: &'static str = "bronie";
+}
+
+/// nazwane zestawy broni gracza według identyfikatorów broni, używane w `bitwa --zestaw`.
+type ZestawyGracza = BTreeMap<String, Vec<u64>>;
+
+struct ZestawyGraczaHolder;
+
+impl TypeMapKey for ZestawyGraczaHolder {
+    type Value = HashMap<UserId, ZestawyGracza>;
+}
+
+impl Tabela for ZestawyGraczaHolder {
+    const NAZWA: &'static str = "zestawy";
+}
+
+// osłony, jak bronie, są tożsame według identyfikatora, a wyświetlane według stanu i nazwy
+#[derive(Derivative, Serialize, Deserialize)]
+#[derivative(PartialEq, Eq, Hash)]
+struct OsłonaGracza {
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    osłona: zug::Osłona,
+
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    założona: bool,
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    nazwa: Arc<str>,
+    /// stały identyfikator, nadawany również osłonom zapisanym przed jego wprowadzeniem.
+    #[serde(default = "rand::random")]
+    id: u64,
+}
+
+impl fmt::Display for OsłonaGracza {
+    #[allow(clippy::match_bool)] // i think this is more readable
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}) : {}, {}, trwałość {}",
+            self.nazwa,
+            match self.założona {
+                true => "założona",
+                false => "zdjęta",
+            },
+            self.osłona.rodzaj_str(),
+            self.osłona.jakość.nazwa(),
+            self.osłona.trwałość
+        )
+    }
+}
+
+impl OsłonaGracza {
+    #[allow(clippy::match_bool)] // i think this is more readable
+    fn opis(&self, mowa: &Mowa) -> String {
+        let stan = match self.założona {
+            true => "osłona.założona",
+            false => "osłona.zdjęta",
+        };
+        let wartości = wartości_osłony(&self.osłona, mowa);
+        opis_sprzętu(mowa, "osłona.opis", &self.nazwa, Some(stan), &wartości)
+    }
+}
+
+type OsłonyGracza = HashSet<OsłonaGracza>;
+
+struct OsłonyGraczaHolder;
+
+impl TypeMapKey for OsłonyGraczaHolder {
+    type Value = HashMap<UserId, OsłonyGracza>;
+}
+
+impl Tabela for OsłonyGraczaHolder {
+    const NAZWA: &'static str = "osłony";
+}
+
+/// szablon sprzętu w arsenale gildii. gracze biorą jego kopie, więc późniejsze zmiany
+/// szablonu nie dotyczą wziętego sprzętu.
+#[derive(Clone, Serialize, Deserialize)]
+enum Szablon {
+    Broń(zug::Broń),
+    Osłona(zug::Osłona),
+}
+
+impl Szablon {
+    fn opis(&self, nazwa: &str, mowa: &Mowa) -> String {
+        let (klucz, wartości) = match self {
+            Self::Broń(broń) => ("arsenał.broń", wartości_broni(broń, mowa)),
+            Self::Osłona(osłona) => ("arsenał.osłona", wartości_osłony(osłona, mowa)),
+        };
+        opis_sprzętu(mowa, klucz, nazwa, None, &wartości)
+    }
+}
+
+type Arsenał = BTreeMap<String, Szablon>;
+
+struct ArsenałHolder;
+
+impl TypeMapKey for ArsenałHolder {
+    type Value = HashMap<GuildId, Arsenał>;
+}
+
+impl Tabela for ArsenałHolder {
+    const NAZWA: &'static str = "arsenał";
+}
+
+/// ostatnia próba lub bitwa gracza wraz z historią jej przerzutów.
+struct OstatnieRzutyHolder;
+
+impl TypeMapKey for OstatnieRzutyHolder {
+    type Value = HashMap<UserId, zug::Wynik>;
+}
+
+impl Tabela for OstatnieRzutyHolder {
+    const NAZWA: &'static str = "rzuty";
+}
+
 /* mięsko */
 
 #[group]
-#[prefixes("kobler", "k")]
-#[commands(kurwa, wzorzec, broń, próba, bitwa, zanik)]
+#[commands(
+    kurwa,
+    wzorzec,
+    broń,
+    osłona,
+    arsenał,
+    próba,
+    pomagam,
+    bitwa,
+    przerzuć,
+    pula,
+    zanik,
+    ustawienia,
+    preferencje
+)]
 struct General;
 
-struct Handler;
+struct Handler {
+    powitanie: Powitanie,
+    powitane: Mutex<HashSet<GuildId>>,
+}
+
+impl Handler {
+    /// kanał powitania gildii, o ile bot może na nim pisać. bez ustawionego kanału
+    /// korzysta z kanału systemowego gildii, nigdy z przypadkowego.
+    async fn kanał_powitania(&self, ctx: &Context, guild: &Guild) -> Option<ChannelId> {
+        let kanał = match self.powitanie.kanał(guild.id) {
+            KanałPowitania::Wybrany(kanał) => kanał,
+            KanałPowitania::Domyślny => guild.system_channel_id?,
+            KanałPowitania::Brak => return None,
+        };
+        let kanał = guild.channels.get(&kanał)?;
+        let bot = ctx.cache.current_user_id().await;
+        match kanał.permissions_for_user(ctx, bot).await {
+            Ok(uprawnienia) if uprawnienia.read_messages() && uprawnienia.send_messages() => {
+                Some(kanał.id)
+            }
+            Ok(_) => {
+                tracing::warn!(
+                    gildia = %guild.id,
+                    kanał = %kanał.id,
+                    "brak uprawnień do powitania"
+                );
+                None
+            }
+            Err(why) => {
+                tracing::warn!(
+                    gildia = %guild.id,
+                    błąd = ?why,
+                    "nie udało się sprawdzić uprawnień"
+                );
+                None
+            }
+        }
+    }
+}
 
 #[async_trait]
 impl EventHandler for Handler {
     async fn ready(&self, ctx: Context, ready: Ready) {
-        if let Some(guild) = ctx.cache.guilds().await.pop() {
-            if let Some(channels) = ctx.cache.guild_channels(guild).await {
-                // maybe there is a way to find a better channel to post in
-                if let Some(channel) = channels.values().find(|channel| channel.is_text_based()) {
-                    let message = channel
-                        .send_message(&ctx, |m| m.content(format!("kobler aktywny. {}", KRZYCZ)))
-                        .await;
-                    if let Err(why) = message {
-                        println!("błąd wiadomości: {:?}", why);
-                    };
-                }
-            } else {
-                println!("gildia nie posiada kanałów");
+        tracing::info!(bot = %ready.user.name, gildie = ready.guilds.len(), "aktywny");
+        match interakcje::zarejestruj(&ctx).await {
+            Ok(polecenia) => {
+                tracing::info!(polecenia = polecenia.len(), "zarejestrowano polecenia");
             }
-        } else {
-            println!("nie znaleziono aktywnej gildii");
+            Err(why) => tracing::error!(błąd = ?why, "nie udało się zarejestrować poleceń"),
+        }
+    }
+
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        match interaction {
+            Interaction::ApplicationCommand(polecenie) => {
+                interakcje::polecenie(&ctx, &polecenie).await;
+            }
+            Interaction::Autocomplete(podpowiedź) => {
+                interakcje::podpowiedz(&ctx, &podpowiedź).await;
+            }
+            Interaction::MessageComponent(składnik) => {
+                interakcje::składnik(&ctx, &składnik).await;
+            }
+            _ => {}
         }
-        println!("{} aktywny", ready.user.name);
     }
-}
 
-fn token() -> Result<String, impl std::error::Error> {
-    fs::read_to_string("auth-token.secret")
+    // wywoływane dla każdej gildii po uruchomieniu oraz dla gildii dołączonych w trakcie działania
+    async fn guild_create(&self, ctx: Context, guild: Guild, is_new: bool) {
+        let nowa = self
+            .powitane
+            .lock()
+            .map_or(false, |mut powitane| powitane.insert(guild.id));
+        if !nowa {
+            return;
+        }
+        tracing::info!(
+            gildia = %guild.id,
+            nazwa = %guild.name,
+            dołączona = is_new,
+            "gildia dostępna"
+        );
+
+        if let Some(kanał) = self.kanał_powitania(&ctx, &guild).await {
+            let mowa = ctx
+                .data
+                .read()
+                .await
+                .get::<UstawieniaHolder>()
+                .and_then(|holder| holder.get(&guild.id).cloned())
+                .unwrap_or_default()
+                .mowa();
+            let message = kanał.say(&ctx.http, mowa.tekst("powitanie")).await;
+            if let Err(why) = message {
+                tracing::error!(gildia = %guild.id, błąd = ?why, "błąd wiadomości");
+            };
+        } else {
+            tracing::info!(gildia = %guild.id, "pominięto powitanie gildii");
+        }
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let konfiguracja = Konfiguracja::wczytaj()?;
+    let _strażnicy = dziennik::uruchom(&konfiguracja.dziennik)?;
+    let reguły = Zestawy::wczytaj(&konfiguracja.reguły)?;
+    tracing::info!(zestawy = reguły.nazwane.len(), "wczytano reguły gry");
+
     let framework = StandardFramework::new()
-        .configure(|c| c.prefix(":"))
+        .configure(|c| c.prefix("").dynamic_prefix(prefiks).with_whitespace(true))
+        .on_dispatch_error(dispatch_error)
         .unrecognised_command(unknown_command)
+        .before(before)
+        .after(after)
         .group(&GENERAL_GROUP);
 
-    let mut client = Client::builder(token()?.trim())
-        .event_handler(Handler)
+    let magazyn = konfiguracja.magazyn;
+    let token = konfiguracja.token.odczytaj()?;
+    let token = token.trim();
+    // polecenia wymagają identyfikatora aplikacji, który discord podaje dla tokenu
+    let aplikacja = Http::new_with_token(token)
+        .get_current_application_info()
+        .await?
+        .id;
+    let mut client = Client::builder(token)
+        .application_id(aplikacja.0)
+        .event_handler(Handler {
+            powitanie: konfiguracja.powitanie,
+            powitane: Mutex::default(),
+        })
         .framework(framework)
-        .type_map_insert::<WzorzecHolder>(HashMap::default())
+        .type_map_insert::<WzorzecHolder>(magazyn.wczytaj(WzorzecHolder::NAZWA)?)
+        .type_map_insert::<BronieGraczaHolder>(magazyn.wczytaj(BronieGraczaHolder::NAZWA)?)
+        .type_map_insert::<ZestawyGraczaHolder>(magazyn.wczytaj(ZestawyGraczaHolder::NAZWA)?)
+        .type_map_insert::<OsłonyGraczaHolder>(magazyn.wczytaj(OsłonyGraczaHolder::NAZWA)?)
+        .type_map_insert::<ArsenałHolder>(magazyn.wczytaj(ArsenałHolder::NAZWA)?)
+        .type_map_insert::<OstatnieRzutyHolder>(magazyn.wczytaj(OstatnieRzutyHolder::NAZWA)?)
+        .type_map_insert::<UstawieniaHolder>(magazyn.wczytaj(UstawieniaHolder::NAZWA)?)
+        .type_map_insert::<UstawieniaGraczaHolder>(
+            magazyn.wczytaj(UstawieniaGraczaHolder::NAZWA)?,
+        )
+        .type_map_insert::<PrefiksyHolder>(konfiguracja.prefiksy.pełne())
+        .type_map_insert::<RegułyHolder>(reguły)
+        .type_map_insert::<StartHolder>(HashMap::default())
+        .type_map_insert::<PomocHolder>(HashMap::default())
+        .type_map_insert::<MagazynHolder>(magazyn)
         .await?;
 
     client.start().await?;
@@ -152,347 +710,1692 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 /* ## kurwa */
 
 #[command]
-async fn kurwa(ctx: &Context, msg: &Message) -> CommandResult {
-    let help = fs::read_to_string("readme.md")?;
-    msg.reply(ctx, help).await?;
+async fn kurwa(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let args = argumenty(args)?;
+    let nadawca = Nadawca::from(msg);
+    let mowa = ustawienia::mowa(ctx, &nadawca).await;
+    let części = match args.first() {
+        Some(Arg::Plain(nazwa)) => {
+            if let Some(komenda) = komendy::Komenda::znajdź(nazwa) {
+                // przykłady pokazują prefiks, którym wywołano wsparcie
+                let prefiksy = prefiksy(ctx, &nadawca).await;
+                let prefiks = prefiksy
+                    .iter()
+                    .find(|prefiks| msg.content.starts_with(prefiks.as_str()))
+                    .or_else(|| prefiksy.first())
+                    .map_or("", String::as_str);
+                komendy::podziel_blok(&komenda.pomoc(mowa.język, prefiks))
+            } else {
+                vec![mowa.wstaw("błąd.nieznana_komenda", &[("komenda", nazwa)])]
+            }
+        }
+        _ => komendy::podziel(&komendy::przegląd(mowa.język), komendy::LIMIT_WIADOMOŚCI),
+    };
+    for część in części {
+        msg.reply(ctx, część).await?;
+    }
 
     Ok(())
 }
 
 /* ## wzorzec */
 
-#[command]
 #[allow(clippy::significant_drop_tightening)] // compiler errors if sugestion followed
-async fn wzorzec(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+async fn obsłuż_wzorzec(ctx: &Context, nadawca: &Nadawca, args: &[Arg]) -> Result<String, Błąd> {
+    let mowa = ustawienia::mowa(ctx, nadawca).await;
+    let reguły = ustawienia::reguły(ctx, nadawca).await;
     let mut data = ctx.data.write().await;
     let holder = data
         .get_mut::<WzorzecHolder>()
-        .expect("spodziewano się WzorzecHolder w TypeMap.");
+        .ok_or_else(|| Błąd::Magazyn("brak WzorzecHolder w TypeMap".to_owned()))?;
 
-    let args = Arg::try_parse(args)?;
     match args.first() {
         Some(arg) => {
-            if let Some(wzorzec) = zug::Wzorzec::try_parse(arg) {
-                if let Some(entry) = holder.insert(msg.author.id, wzorzec) {
-                    msg.reply(
-                        ctx,
-                        format!("zamieniono wzorzec z {} na {}.", entry, wzorzec),
+            let wzorzec = zug::Wzorzec::try_parse(arg, &reguły)
+                .ok_or_else(|| Błąd::NiepoprawnaWartość("wzorzec", arg.to_string()))?;
+            dziennik::audyt(nadawca, "wzorzec", &wzorzec.to_string());
+            let entry = holder.insert(nadawca.gracz, wzorzec.clone());
+            zachowaj::<WzorzecHolder>(&data)?;
+            let wzorzec = wzorzec.to_string();
+            Ok(entry.map_or_else(
+                || mowa.wstaw("wzorzec.zapisano", &[("wzorzec", &wzorzec)]),
+                |entry| {
+                    mowa.wstaw(
+                        "wzorzec.zamieniono",
+                        &[("stary", &entry.to_string()), ("wzorzec", &wzorzec)],
                     )
-                    .await?
-                } else {
-                    msg.reply(ctx, format!("zapisano wzorzec {}.", wzorzec))
-                        .await?
-                }
-            } else {
-                msg.reply(ctx, format!("argument niepoprawny. {}", KRZYCZ))
-                    .await?
-            }
+                },
+            ))
         }
         None => {
-            if let Some(wzorzec) = holder.get(&msg.author.id) {
-                msg.reply(ctx, format!("twój wzorzec to {}", wzorzec))
-                    .await?
-            } else {
-                msg.reply(ctx, "nie posiadasz prawzoru.").await?
-            }
+            let wzorzec = holder.get(&nadawca.gracz).ok_or(Błąd::BrakWzorca)?;
+            Ok(mowa.wstaw("wzorzec.twój", &[("wzorzec", &wzorzec.to_string())]))
         }
-    };
+    }
+}
+
+#[command]
+async fn wzorzec(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let odpowiedź = obsłuż_wzorzec(ctx, &Nadawca::from(msg), &argumenty(args)?).await?;
+    msg.reply(ctx, odpowiedź).await?;
 
     Ok(())
 }
 
-/* ## broń */
+/* ## arsenał */
 
-async fn broń_helper<F>(
+/// szablon wybranego rodzaju z arsenału gildii, o nazwie najbliższej podanej po podkomendzie.
+async fn z_arsenału<T, F>(
     ctx: &Context,
-    msg: &Message,
-    args: Vec<Arg>,
-    holder: &mut <BronieGraczaHolder as TypeMapKey>::Value,
-    action: F,
-    msg_on_success: &str,
-    readd: bool,
-) -> CommandResult
+    nadawca: &Nadawca,
+    args: &[Arg],
+    rodzaj: F,
+) -> Result<(String, T), Błąd>
 where
-    F: Fn(&mut BrońGracza) + Send,
+    F: Fn(&Szablon) -> Option<T>,
 {
-    if let Some(options) = args
-        .iter()
-        .filter_map(|arg| match arg {
-            Arg::Short('n', options) => Some(options),
-            Arg::Long(param, options) => {
-                if dist(param, "nazwa") < 3 {
-                    Some(options)
-                } else {
-                    None
+    let gildia = nadawca.gildia.ok_or(Błąd::Niedozwolone("gildia"))?;
+    let Some(Arg::Plain(nazwa)) = args.get(1) else {
+        return Err(Błąd::BrakArgumentu("szablon"));
+    };
+    let data = ctx.data.read().await;
+    data.get::<ArsenałHolder>()
+        .and_then(|holder| holder.get(&gildia))
+        .into_iter()
+        .flatten()
+        .filter_map(|(inna, szablon)| Some((dist(inna, nazwa), inna, rodzaj(szablon)?)))
+        .filter(|(odległość, ..)| *odległość < 3)
+        .min_by_key(|(odległość, ..)| *odległość)
+        .map(|(_, inna, szablon)| (inna.clone(), szablon))
+        .ok_or_else(|| Błąd::NieznanySzablon(nazwa.clone()))
+}
+
+#[allow(clippy::significant_drop_tightening)] // compiler errors if sugestion followed
+async fn obsłuż_arsenał(ctx: &Context, nadawca: &Nadawca, args: &[Arg]) -> Result<String, Błąd> {
+    let gildia = nadawca.gildia.ok_or(Błąd::Niedozwolone("gildia"))?;
+    let mowa = ustawienia::mowa(ctx, nadawca).await;
+    // arsenał przegląda każdy, ale zmienia tylko mistrz gry lub, bez jego roli, administrator
+    if !args.is_empty() && !ustawienia::czy_zarządca(ctx, nadawca).await {
+        return Err(Błąd::Niedozwolone("arsenał"));
+    }
+    let mut data = ctx.data.write().await;
+    let holder = data
+        .get_mut::<ArsenałHolder>()
+        .ok_or_else(|| Błąd::Magazyn("brak ArsenałHolder w TypeMap".to_owned()))?;
+
+    match args.first() {
+        Some(Arg::Plain(cmd)) => {
+            let arsenał = holder.entry(gildia).or_default();
+            let nazwa = nazwa(args)?.to_owned();
+            let klucz = match cmd.as_str() {
+                "broń" => {
+                    arsenał.insert(nazwa.clone(), Szablon::Broń(zug::Broń::try_parse(args)?));
+                    "arsenał.zapisano"
                 }
-            }
-            _ => None,
-        })
-        .last() && let Some(nazwa) = options.last()
-    {
-        if let Some(bronie) = holder.get_mut(&msg.author.id) {
-            if let Some(mut broń) = bronie
-                .extract_if(|broń| dist(&broń.nazwa, nazwa) < 3)
-                .last()
-            {
-                action(&mut broń);
-                if readd {bronie.insert(broń);}
-                msg.reply(ctx, msg_on_success).await?;
-            } else {
-                msg.reply(ctx, "nie posiadasz broni o podanej nazwie.")
-                    .await?;
-            }
-        } else {
-            msg.reply(ctx, "nie posiadasz żadnej broni.").await?;
+                "osłona" => {
+                    arsenał.insert(nazwa.clone(), Szablon::Osłona(zug::Osłona::try_parse(args)?));
+                    "arsenał.zapisano"
+                }
+                "usuń" => {
+                    arsenał
+                        .remove(&nazwa)
+                        .ok_or_else(|| Błąd::NieznanySzablon(nazwa.clone()))?;
+                    "arsenał.usunięto"
+                }
+                _ => return Err(Błąd::Argument(InvalidArgument::new(cmd.clone()))),
+            };
+            dziennik::audyt(nadawca, klucz, &nazwa);
+            zachowaj::<ArsenałHolder>(&data)?;
+            Ok(mowa.wstaw(klucz, &[("nazwa", &nazwa)]))
         }
-    } else {
-        msg.reply(ctx, format!("nie podano argumentu nazwy. {}", KRZYCZ))
-            .await?;
+        Some(_) => Err(Błąd::BrakArgumentu("podkomenda")),
+        None => Ok(holder
+            .get(&gildia)
+            .filter(|arsenał| !arsenał.is_empty())
+            .map_or_else(
+                || mowa.tekst("arsenał.pusty"),
+                |arsenał| {
+                    let szablony = arsenał
+                        .iter()
+                        .map(|(nazwa, szablon)| szablon.opis(nazwa, &mowa))
+                        .join("\n");
+                    format!("```\n{szablony}\n```")
+                },
+            )),
     }
+}
+
+#[command]
+#[only_in(guilds)]
+async fn arsenał(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let odpowiedź = obsłuż_arsenał(ctx, &Nadawca::from(msg), &argumenty(args)?).await?;
+    msg.reply(ctx, odpowiedź).await?;
 
     Ok(())
 }
 
-async fn broń_dodaj(
-    ctx: &Context,
-    msg: &Message,
-    args: Vec<Arg>,
-    holder: &mut <BronieGraczaHolder as TypeMapKey>::Value,
-) -> CommandResult {
-    if let Some(options) = args
+/* ## broń */
+
+/// identyfikator broni gracza o podanej nazwie. bez dokładnego dopasowania szuka nazw
+/// podobnych, a gdy pasuje kilka, prosi gracza o wybór zamiast zgadywać.
+fn znajdź_broń(bronie: &BronieGracza, nazwa: &str) -> Result<u64, Błąd> {
+    if let Some(broń) = bronie.iter().find(|broń| &*broń.nazwa == nazwa) {
+        return Ok(broń.id);
+    }
+    let kandydatki = bronie
         .iter()
-        .filter_map(|arg| match arg {
-            Arg::Short('n', options) => Some(options),
-            Arg::Long(param, options) => {
-                (dist(param, "nazwa") < 3).then_some(options) 
-            }
-            _ => None,
-        })
-        .last() && let Some(nazwa) = options.last()
-    {
-        if let Ok(broń) = zug::Broń::try_parse(&args)  {
-            let broń_gracza = BrońGracza {nazwa: Arc::from(nazwa.as_str()), aktywna: false, broń};
-            if let Some(bronie) = holder.get_mut(&msg.author.id) {
-                bronie.insert(broń_gracza);
-            } else {
-                let mut bronie = HashSet::new();
-                bronie.insert(broń_gracza);
-                holder.insert(msg.author.id, bronie);
-            }
-            msg.reply(ctx, "dodano broń.").await?;
-        } else {
-            msg.reply(ctx, format!("podano niepoprawny argument broni. {}", KRZYCZ)).await?;
-        }
-    } else {
-        msg.reply(ctx, format!("nie podano argumentu nazwy. {}", KRZYCZ))
-            .await?;
+        .filter(|broń| dist(&broń.nazwa, nazwa) < 3)
+        .collect::<Vec<_>>();
+    match kandydatki.as_slice() {
+        [] => Err(Błąd::NieznanaBroń(nazwa.to_owned())),
+        [broń] => Ok(broń.id),
+        _ => Err(Błąd::NiejednoznacznaBroń(
+            nazwa.to_owned(),
+            kandydatki.iter().map(|broń| broń.nazwa.to_string()).sorted().collect(),
+        )),
     }
+}
 
+/// nazwy broni są unikalne wśród broni gracza.
+fn sprawdź_nazwę(bronie: Option<&BronieGracza>, nazwa: &str) -> Result<(), Błąd> {
+    if bronie.into_iter().flatten().any(|broń| &*broń.nazwa == nazwa) {
+        return Err(Błąd::ZajętaNazwa(nazwa.to_owned()));
+    }
     Ok(())
 }
 
-async fn broń_wybierz(
-    ctx: &Context,
-    msg: &Message,
-    args: Vec<Arg>,
-    holder: &mut <BronieGraczaHolder as TypeMapKey>::Value,
-) -> CommandResult {
-    broń_helper(
-        ctx,
-        msg,
-        args,
-        holder,
-        |broń| broń.aktywna = true,
-        "wybrano broń.",
-        true,
-    )
-    .await
+/// identyfikatory broni wskazanych nazwami lub wszystkich przy `--wszystkie`. nazwy
+/// sprawdzane są przed zmianą, by błąd w jednej nie zmienił pozostałych.
+fn wskazane(bronie: &BronieGracza, args: &[Arg]) -> Result<Vec<u64>, Błąd> {
+    if args.iter().any(|arg| komendy::WSZYSTKIE.pasuje(arg)) {
+        return Ok(bronie.iter().map(|broń| broń.id).collect());
+    }
+    nazwy(args)?
+        .into_iter()
+        .map(|nazwa| znajdź_broń(bronie, nazwa))
+        .collect()
 }
 
-async fn broń_schowaj(
-    ctx: &Context,
-    msg: &Message,
-    args: Vec<Arg>,
+/// identyfikator jednej broni wskazanej nazwą, dla zmian, które nie dotyczą wielu broni
+/// naraz, jak usunięcie.
+fn wskazana(bronie: &BronieGracza, args: &[Arg]) -> Result<Vec<u64>, Błąd> {
+    if args.iter().any(|arg| komendy::WSZYSTKIE.pasuje(arg)) {
+        return Err(Błąd::Niedozwolone("wszystkie"));
+    }
+    match nazwy(args)?.as_slice() {
+        [nazwa] => Ok(vec![znajdź_broń(bronie, nazwa)?]),
+        nazwy => Err(Błąd::NiepoprawnaWartość("nazwa", nazwy.join(" "))),
+    }
+}
+
+/// wykonuje `action` na broniach o podanych identyfikatorach, zwracając odpowiedź z ich
+/// nazwami.
+fn broń_helper<F>(
+    nadawca: &Nadawca,
+    mowa: &Mowa,
+    wybrane: &[u64],
     holder: &mut <BronieGraczaHolder as TypeMapKey>::Value,
-) -> CommandResult {
-    broń_helper(
-        ctx,
-        msg,
-        args,
-        holder,
+    action: F,
+    msg_on_success: &'static str,
+    readd: bool,
+) -> Result<String, Błąd>
+where
+    F: Fn(&mut BrońGracza),
+{
+    let bronie = holder.get_mut(&nadawca.gracz).ok_or(Błąd::BrakBroni)?;
+    let mut zmienione = bronie
+        .extract_if(|broń| wybrane.contains(&broń.id))
+        .collect::<Vec<_>>();
+    for broń in &mut zmienione {
+        action(broń);
+        dziennik::audyt(nadawca, msg_on_success, &broń.to_string());
+    }
+    let nazwy = zmienione.iter().map(|broń| &broń.nazwa).sorted().join(", ");
+    if readd {
+        bronie.extend(zmienione);
+    }
+
+    Ok(mowa.wstaw(msg_on_success, &[("bronie", &nazwy)]))
+}
+
+fn broń_dodaj(
+    nadawca: &Nadawca,
+    mowa: &Mowa,
+    args: &[Arg],
+    holder: &mut <BronieGraczaHolder as TypeMapKey>::Value,
+) -> Result<String, Błąd> {
+    let nazwa = nazwa(args)?;
+    let broń = zug::Broń::try_parse(args)?;
+    sprawdź_nazwę(holder.get(&nadawca.gracz), nazwa)?;
+    let broń_gracza = BrońGracza {
+        nazwa: Arc::from(nazwa),
+        aktywna: false,
+        broń,
+        id: rand::random(),
+    };
+    dziennik::audyt(nadawca, "broń.dodano", &broń_gracza.to_string());
+    holder.entry(nadawca.gracz).or_default().insert(broń_gracza);
+
+    Ok(mowa.tekst("broń.dodano"))
+}
+
+/// kopia broni z arsenału gildii, nazwana jak szablon, o ile nie podano innej nazwy.
+async fn broń_weź(ctx: &Context, nadawca: &Nadawca, args: &[Arg]) -> Result<String, Błąd> {
+    let mowa = ustawienia::mowa(ctx, nadawca).await;
+    let (szablon, broń) = z_arsenału(ctx, nadawca, args, |szablon| match szablon {
+        Szablon::Broń(broń) => Some(broń.clone()),
+        Szablon::Osłona(_) => None,
+    })
+    .await?;
+    let broń_gracza = BrońGracza {
+        nazwa: Arc::from(nazwa(args).unwrap_or(szablon.as_str())),
+        aktywna: false,
+        broń,
+        id: rand::random(),
+    };
+
+    let mut data = ctx.data.write().await;
+    let bronie = data
+        .get_mut::<BronieGraczaHolder>()
+        .ok_or_else(|| Błąd::Magazyn("brak BronieGraczaHolder w TypeMap".to_owned()))?
+        .entry(nadawca.gracz)
+        .or_default();
+    sprawdź_nazwę(Some(bronie), &broń_gracza.nazwa)?;
+    dziennik::audyt(nadawca, "broń.wzięto", &broń_gracza.to_string());
+    bronie.insert(broń_gracza);
+    zachowaj::<BronieGraczaHolder>(&data)?;
+    Ok(mowa.wstaw("broń.wzięto", &[("szablon", &szablon)]))
+}
+
+fn broń_wybierz(
+    nadawca: &Nadawca,
+    mowa: &Mowa,
+    reguły: &Reguły,
+    args: &[Arg],
+    holder: &mut <BronieGraczaHolder as TypeMapKey>::Value,
+) -> Result<String, Błąd> {
+    let bronie = holder.get(&nadawca.gracz).ok_or(Błąd::BrakBroni)?;
+    let wybrane = wskazane(bronie, args)?;
+    // bronie aktywne po wybraniu muszą mieścić się w uzbrojeniu z reguł
+    zug::sprawdź_uzbrojenie(
+        reguły,
+        bronie
+            .iter()
+            .filter(|broń| broń.aktywna || wybrane.contains(&broń.id))
+            .map(|broń| &broń.broń),
+    )?;
+    broń_helper(
+        nadawca,
+        mowa,
+        &wybrane,
+        holder,
+        |broń| broń.aktywna = true,
+        "broń.wybrano",
+        true,
+    )
+}
+
+fn broń_schowaj(
+    nadawca: &Nadawca,
+    mowa: &Mowa,
+    args: &[Arg],
+    holder: &mut <BronieGraczaHolder as TypeMapKey>::Value,
+) -> Result<String, Błąd> {
+    let bronie = holder.get(&nadawca.gracz).ok_or(Błąd::BrakBroni)?;
+    let wybrane = wskazane(bronie, args)?;
+    broń_helper(
+        nadawca,
+        mowa,
+        &wybrane,
+        holder,
         |broń| broń.aktywna = false,
-        "schowano broń.",
+        "broń.schowano",
         true,
     )
-    .await
 }
 
-async fn broń_usuń(
-    ctx: &Context,
-    msg: &Message,
-    args: Vec<Arg>,
+fn broń_zmień(
+    nadawca: &Nadawca,
+    mowa: &Mowa,
+    reguły: &Reguły,
+    args: &[Arg],
     holder: &mut <BronieGraczaHolder as TypeMapKey>::Value,
-) -> CommandResult {
-    broń_helper(ctx, msg, args, holder, |_| {}, "usunięto broń.", false).await
+) -> Result<String, Błąd> {
+    let zmiana = zug::ZmianaBroni::try_parse(args)?;
+    let bronie = holder.get(&nadawca.gracz).ok_or(Błąd::BrakBroni)?;
+    let wybrane = wskazana(bronie, args)?;
+    // zmieniona broń aktywna, np. cięższa, musi nadal mieścić się w uzbrojeniu z reguł
+    let aktywne = bronie
+        .iter()
+        .filter(|broń| broń.aktywna)
+        .map(|broń| {
+            let mut zmieniona = broń.broń.clone();
+            if wybrane.contains(&broń.id) {
+                zmiana.zastosuj(&mut zmieniona);
+            }
+            zmieniona
+        })
+        .collect::<Vec<_>>();
+    zug::sprawdź_uzbrojenie(reguły, aktywne.iter())?;
+    broń_helper(
+        nadawca,
+        mowa,
+        &wybrane,
+        holder,
+        |broń| zmiana.zastosuj(&mut broń.broń),
+        "broń.zmieniono",
+        true,
+    )
 }
 
-#[command]
+fn broń_przemianuj(
+    nadawca: &Nadawca,
+    mowa: &Mowa,
+    args: &[Arg],
+    holder: &mut <BronieGraczaHolder as TypeMapKey>::Value,
+) -> Result<String, Błąd> {
+    let nowa = args
+        .iter()
+        .filter_map(|arg| komendy::NOWA.dopasuj(arg))
+        .next_back()
+        .and_then(<[String]>::last)
+        .ok_or(Błąd::BrakArgumentu("nowa_nazwa"))?;
+    let bronie = holder.get_mut(&nadawca.gracz).ok_or(Błąd::BrakBroni)?;
+    let id = znajdź_broń(bronie, nazwa(args)?)?;
+    if bronie.iter().any(|broń| &*broń.nazwa == nowa && broń.id != id) {
+        return Err(Błąd::ZajętaNazwa(nowa.clone()));
+    }
+    let mut broń = bronie
+        .extract_if(|broń| broń.id == id)
+        .next()
+        .ok_or(Błąd::BrakBroni)?;
+    let stara = std::mem::replace(&mut broń.nazwa, Arc::from(nowa.as_str()));
+    dziennik::audyt(nadawca, "broń.przemianowano", &format!("{stara} -> {broń}"));
+    bronie.insert(broń);
+
+    Ok(mowa.wstaw("broń.przemianowano", &[("stara", &stara), ("nazwa", nowa)]))
+}
+
+/// zapisuje zestaw wskazanych broni, usuwa zestaw podany bez broni lub wyświetla zestawy gracza.
 #[allow(clippy::significant_drop_tightening)] // compiler errors if sugestion followed
-async fn broń(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+async fn broń_zestaw(ctx: &Context, nadawca: &Nadawca, args: &[Arg]) -> Result<String, Błąd> {
+    let mowa = ustawienia::mowa(ctx, nadawca).await;
+    let reguły = ustawienia::reguły(ctx, nadawca).await;
+    let mut data = ctx.data.write().await;
+    let bronie = data
+        .get::<BronieGraczaHolder>()
+        .ok_or_else(|| Błąd::Magazyn("brak BronieGraczaHolder w TypeMap".to_owned()))?
+        .get(&nadawca.gracz)
+        .ok_or(Błąd::BrakBroni)?;
+    // w poleceniach discorda nazwa zestawu może wystąpić po nazwach broni
+    let zestaw = args.iter().skip(1).find_map(|arg| match arg {
+        Arg::Plain(zestaw) => Some(zestaw),
+        _ => None,
+    });
+    let Some(zestaw) = zestaw else {
+        return Ok(data
+            .get::<ZestawyGraczaHolder>()
+            .and_then(|holder| holder.get(&nadawca.gracz))
+            .filter(|zestawy| !zestawy.is_empty())
+            .map_or_else(
+                || mowa.tekst("broń.brak_zestawów"),
+                |zestawy| {
+                    let opisy = zestawy
+                        .iter()
+                        .map(|(zestaw, ids)| {
+                            format!("{zestaw} : {}", nazwy_zestawu(bronie, ids).join(", "))
+                        })
+                        .join("\n");
+                    format!("```\n{opisy}\n```")
+                },
+            ));
+    };
+    let zestaw = zestaw.clone();
+    let podane = args
+        .iter()
+        .any(|arg| komendy::NAZWA.pasuje(arg) || komendy::WSZYSTKIE.pasuje(arg));
+    let wybrane = if podane {
+        let wybrane = wskazane(bronie, args)?;
+        zug::sprawdź_uzbrojenie(
+            &reguły,
+            bronie
+                .iter()
+                .filter(|broń| wybrane.contains(&broń.id))
+                .map(|broń| &broń.broń),
+        )?;
+        Some((nazwy_zestawu(bronie, &wybrane).join(", "), wybrane))
+    } else {
+        None
+    };
+
+    let zestawy = data
+        .get_mut::<ZestawyGraczaHolder>()
+        .ok_or_else(|| Błąd::Magazyn("brak ZestawyGraczaHolder w TypeMap".to_owned()))?
+        .entry(nadawca.gracz)
+        .or_default();
+    let odpowiedź = match wybrane {
+        Some((nazwy, wybrane)) => {
+            dziennik::audyt(nadawca, "broń.zestaw", &format!("{zestaw} : {nazwy}"));
+            zestawy.insert(zestaw.clone(), wybrane);
+            mowa.wstaw("broń.zestaw", &[("zestaw", &zestaw), ("bronie", &nazwy)])
+        }
+        None => {
+            zestawy
+                .remove(&zestaw)
+                .ok_or_else(|| Błąd::NieznanyZestaw(zestaw.clone()))?;
+            dziennik::audyt(nadawca, "broń.zestaw_usunięty", &zestaw);
+            mowa.wstaw("broń.zestaw_usunięty", &[("zestaw", &zestaw)])
+        }
+    };
+    // identyfikatory broni zapisanych przed ich wprowadzeniem utrwalane są razem z zestawem
+    zachowaj::<BronieGraczaHolder>(&data)?;
+    zachowaj::<ZestawyGraczaHolder>(&data)?;
+    Ok(odpowiedź)
+}
+
+/// nazwy broni zestawu, z pominięciem broni usuniętych od jego zapisania.
+fn nazwy_zestawu(bronie: &BronieGracza, ids: &[u64]) -> Vec<String> {
+    bronie
+        .iter()
+        .filter(|broń| ids.contains(&broń.id))
+        .map(|broń| broń.nazwa.to_string())
+        .sorted()
+        .collect()
+}
+
+/// usuwa bronie z zestawów gracza, który przestał je mieć.
+fn usuń_z_zestawów(data: &mut TypeMap, gracz: UserId, usunięte: &[u64]) -> Result<(), Błąd> {
+    let Some(zestawy) = data
+        .get_mut::<ZestawyGraczaHolder>()
+        .and_then(|holder| holder.get_mut(&gracz))
+    else {
+        return Ok(());
+    };
+    for ids in zestawy.values_mut() {
+        ids.retain(|id| !usunięte.contains(id));
+    }
+    zachowaj::<ZestawyGraczaHolder>(data)
+}
+
+/// usuwa wskazaną broń gracza, również z jego zestawów.
+#[allow(clippy::significant_drop_tightening)] // compiler errors if sugestion followed
+async fn broń_usuń(ctx: &Context, nadawca: &Nadawca, args: &[Arg]) -> Result<String, Błąd> {
+    let mowa = ustawienia::mowa(ctx, nadawca).await;
+    let mut data = ctx.data.write().await;
+    let holder = data
+        .get_mut::<BronieGraczaHolder>()
+        .ok_or_else(|| Błąd::Magazyn("brak BronieGraczaHolder w TypeMap".to_owned()))?;
+    let bronie = holder.get(&nadawca.gracz).ok_or(Błąd::BrakBroni)?;
+    let wybrane = wskazana(bronie, args)?;
+    let odpowiedź =
+        broń_helper(nadawca, &mowa, &wybrane, holder, |_| {}, "broń.usunięto", false)?;
+    zachowaj::<BronieGraczaHolder>(&data)?;
+    usuń_z_zestawów(&mut data, nadawca.gracz, &wybrane)?;
+
+    Ok(odpowiedź)
+}
+
+#[allow(clippy::significant_drop_tightening)] // compiler errors if sugestion followed
+async fn obsłuż_broń(ctx: &Context, nadawca: &Nadawca, args: &[Arg]) -> Result<String, Błąd> {
+    // arsenał odczytywany jest przed zablokowaniem broni graczy do zapisu
+    if let Some(Arg::Plain(cmd)) = args.first()
+        && cmd == "weź"
+    {
+        return broń_weź(ctx, nadawca, args).await;
+    }
+    if let Some(Arg::Plain(cmd)) = args.first()
+        && cmd == "zestaw"
+    {
+        return broń_zestaw(ctx, nadawca, args).await;
+    }
+    // usunięta broń znika też z zestawów, więc podkomenda sama blokuje oba rejestry
+    if let Some(Arg::Plain(cmd)) = args.first()
+        && cmd == "usuń"
+    {
+        return broń_usuń(ctx, nadawca, args).await;
+    }
+    let mowa = ustawienia::mowa(ctx, nadawca).await;
+    let reguły = ustawienia::reguły(ctx, nadawca).await;
     let mut data = ctx.data.write().await;
     let holder = data
         .get_mut::<BronieGraczaHolder>()
-        .expect("spodziewano się WzorzecHolder w TypeMap.");
+        .ok_or_else(|| Błąd::Magazyn("brak BronieGraczaHolder w TypeMap".to_owned()))?;
 
-    let args = Arg::try_parse(args)?;
     match args.first() {
-        Some(arg) => if let Arg::Plain(cmd) = arg {
-            match cmd.as_str() {
-                "dodaj" => broń_dodaj(ctx, msg, args, holder).await?,
-                "wybierz" => broń_wybierz(ctx, msg, args, holder).await?,
-                "schowaj" => broń_schowaj(ctx, msg, args, holder).await?,
-                "usuń" => broń_usuń(ctx, msg, args, holder).await?,
-                _ => {
-                    msg.reply(ctx, format!("argument niepoprawny. {}", KRZYCZ)).await?;
-                },
-            }
-        } else {
-            msg.reply(ctx, format!("argument niepoprawny. {}", KRZYCZ)).await?;
-        },
+        Some(Arg::Plain(cmd)) => {
+            let odpowiedź = match cmd.as_str() {
+                "dodaj" => broń_dodaj(nadawca, &mowa, args, holder)?,
+                "wybierz" => broń_wybierz(nadawca, &mowa, &reguły, args, holder)?,
+                "schowaj" => broń_schowaj(nadawca, &mowa, args, holder)?,
+                "zmień" => broń_zmień(nadawca, &mowa, &reguły, args, holder)?,
+                "przemianuj" => broń_przemianuj(nadawca, &mowa, args, holder)?,
+                _ => return Err(Błąd::Argument(InvalidArgument::new(cmd.clone()))),
+            };
+            zachowaj::<BronieGraczaHolder>(&data)?;
+            Ok(odpowiedź)
+        }
+        Some(_) => Err(Błąd::BrakArgumentu("podkomenda")),
         None => {
-            if let Some(bronie) = holder.get(&msg.author.id) && !bronie.is_empty() {
-                msg.reply(ctx,
-                          bronie
-                            .iter()
-                            .sorted()
-                            .map(|broń_gracza| format!("```\n{broń_gracza}\n```"))
-                            .join("\n")
-                    ).await?;
-            } else {
-                msg.reply(ctx, "nie posiadasz żadnej broni.").await?;
+            let bronie = holder
+                .get(&nadawca.gracz)
+                .filter(|bronie| !bronie.is_empty())
+                .ok_or(Błąd::BrakBroni)?;
+            Ok(bronie
+                .iter()
+                .sorted_by_key(|broń| (broń.aktywna, broń.nazwa.clone()))
+                .map(|broń_gracza| format!("```\n{}\n```", broń_gracza.opis(&mowa)))
+                .join("\n"))
+        }
+    }
+}
+
+/// oferuje broń graczowi podanemu wzmianką. broń przechodzi do niego dopiero, gdy potwierdzi
+/// przyjęcie przyciskiem.
+async fn broń_oddaj(ctx: &Context, nadawca: &Nadawca, args: &[Arg]) -> Result<Odpowiedź, Błąd> {
+    let mowa = ustawienia::mowa(ctx, nadawca).await;
+    // nazwa broni może być argumentem, jak w `broń oddaj miecz @gracz`, albo opcją
+    let mut odbiorca = None;
+    let mut nazwa_broni = nazwa(args).ok();
+    for arg in args.iter().skip(1) {
+        if let Arg::Plain(arg) = arg {
+            match parse_username(arg) {
+                Some(gracz) => odbiorca = Some(UserId(gracz)),
+                None => nazwa_broni = nazwa_broni.or(Some(arg.as_str())),
             }
-        },
+        }
+    }
+    let odbiorca = odbiorca.ok_or(Błąd::BrakArgumentu("gracz"))?;
+    let nazwa_broni = nazwa_broni.ok_or(Błąd::BrakArgumentu("nazwa"))?;
+    if odbiorca == nadawca.gracz {
+        return Err(Błąd::NiepoprawnaWartość("gracz", odbiorca.mention().to_string()));
+    }
+
+    let data = ctx.data.read().await;
+    let bronie = data
+        .get::<BronieGraczaHolder>()
+        .ok_or_else(|| Błąd::Magazyn("brak BronieGraczaHolder w TypeMap".to_owned()))?
+        .get(&nadawca.gracz)
+        .ok_or(Błąd::BrakBroni)?;
+    let id = znajdź_broń(bronie, nazwa_broni)?;
+    let broń = bronie
+        .iter()
+        .find(|broń| broń.id == id)
+        .ok_or(Błąd::BrakBroni)?;
+    dziennik::audyt(nadawca, "broń.oferta", &format!("{odbiorca}: {broń}"));
+    let treść = mowa.wstaw(
+        "broń.oferta",
+        &[
+            ("dawca", &nadawca.gracz.mention().to_string()),
+            ("odbiorca", &odbiorca.mention().to_string()),
+            ("nazwa", &broń.nazwa),
+        ],
+    );
+    drop(data);
+    Ok(Odpowiedź {
+        przyciski: Some(interakcje::przyciski_oddania(&mowa, nadawca.gracz, id, odbiorca)),
+        ..Odpowiedź::from(treść)
+    })
+}
+
+/// przenosi broń wraz z jej cechami od oddającego do potwierdzającego odbiorcy.
+#[allow(clippy::significant_drop_tightening)] // compiler errors if sugestion followed
+async fn przekaż_broń(
+    ctx: &Context,
+    nadawca: &Nadawca,
+    mowa: &Mowa,
+    dawca: UserId,
+    id: u64,
+) -> Result<String, Błąd> {
+    let mut data = ctx.data.write().await;
+    let holder = data
+        .get_mut::<BronieGraczaHolder>()
+        .ok_or_else(|| Błąd::Magazyn("brak BronieGraczaHolder w TypeMap".to_owned()))?;
+    // broń mogła zostać usunięta lub oddana, zanim odbiorca ją przyjął
+    let nazwa = holder
+        .get(&dawca)
+        .and_then(|bronie| bronie.iter().find(|broń| broń.id == id))
+        .map(|broń| broń.nazwa.clone())
+        .ok_or(Błąd::Niedozwolone("nieaktualne"))?;
+    sprawdź_nazwę(holder.get(&nadawca.gracz), &nazwa)?;
+    let mut broń = holder
+        .get_mut(&dawca)
+        .and_then(|bronie| bronie.extract_if(|broń| broń.id == id).next())
+        .ok_or(Błąd::Niedozwolone("nieaktualne"))?;
+    broń.aktywna = false;
+    dziennik::audyt(nadawca, "broń.oddano", &format!("{dawca} -> {}: {broń}", nadawca.gracz));
+    holder.entry(nadawca.gracz).or_default().insert(broń);
+    zachowaj::<BronieGraczaHolder>(&data)?;
+    usuń_z_zestawów(&mut data, dawca, &[id])?;
+
+    Ok(mowa.wstaw(
+        "broń.oddano",
+        &[
+            ("dawca", &dawca.mention().to_string()),
+            ("odbiorca", &nadawca.gracz.mention().to_string()),
+            ("nazwa", &nazwa),
+        ],
+    ))
+}
+
+#[command]
+async fn broń(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let nadawca = Nadawca::from(msg);
+    let args = argumenty(args)?;
+    if let Some(Arg::Plain(cmd)) = args.first()
+        && cmd == "oddaj"
+    {
+        let oferta = broń_oddaj(ctx, &nadawca, &args).await?;
+        msg.channel_id
+            .send_message(ctx, |m| oferta.wiadomość(m).reference_message(msg))
+            .await?;
+        return Ok(());
     }
+    let odpowiedź = obsłuż_broń(ctx, &nadawca, &args).await?;
+    msg.reply(ctx, odpowiedź).await?;
 
     Ok(())
 }
 
-/* ## próba */
+/* ## osłona */
+
+/// identyfikator osłony gracza o podanej nazwie, wyszukiwanej tak jak broń w [`znajdź_broń`].
+fn znajdź_osłonę(osłony: &OsłonyGracza, nazwa: &str) -> Result<u64, Błąd> {
+    if let Some(osłona) = osłony.iter().find(|osłona| &*osłona.nazwa == nazwa) {
+        return Ok(osłona.id);
+    }
+    let kandydatki = osłony
+        .iter()
+        .filter(|osłona| dist(&osłona.nazwa, nazwa) < 3)
+        .collect::<Vec<_>>();
+    match kandydatki.as_slice() {
+        [] => Err(Błąd::NieznanaOsłona(nazwa.to_owned())),
+        [osłona] => Ok(osłona.id),
+        _ => Err(Błąd::NiejednoznacznaOsłona(
+            nazwa.to_owned(),
+            kandydatki.iter().map(|osłona| osłona.nazwa.to_string()).sorted().collect(),
+        )),
+    }
+}
+
+/// wyjmuje z osłon gracza jedną osłonę, wskazaną nazwą.
+fn wyjmij_osłonę(osłony: &mut OsłonyGracza, nazwa: &str) -> Result<OsłonaGracza, Błąd> {
+    let id = znajdź_osłonę(osłony, nazwa)?;
+    osłony
+        .extract_if(|osłona| osłona.id == id)
+        .next()
+        .ok_or_else(|| Błąd::NieznanaOsłona(nazwa.to_owned()))
+}
+
+/// nazwy osłon są unikalne wśród osłon gracza, a osłona o zajętej nazwie nie jest dodawana.
+fn dodaj_osłonę(osłony: &mut OsłonyGracza, osłona: OsłonaGracza) -> Result<(), Błąd> {
+    if osłony.iter().any(|inna| inna.nazwa == osłona.nazwa) {
+        return Err(Błąd::ZajętaNazwaOsłony(osłona.nazwa.to_string()));
+    }
+    let nazwa = osłona.nazwa.to_string();
+    if !osłony.insert(osłona) {
+        return Err(Błąd::ZajętaNazwaOsłony(nazwa));
+    }
+    Ok(())
+}
+
+fn osłona_helper<F>(
+    nadawca: &Nadawca,
+    args: &[Arg],
+    holder: &mut <OsłonyGraczaHolder as TypeMapKey>::Value,
+    action: F,
+    msg_on_success: &'static str,
+    readd: bool,
+) -> Result<&'static str, Błąd>
+where
+    F: Fn(&mut OsłonaGracza),
+{
+    let nazwa = nazwa(args)?;
+    let osłony = holder.get_mut(&nadawca.gracz).ok_or(Błąd::BrakOsłon)?;
+    let mut osłona = wyjmij_osłonę(osłony, nazwa)?;
+    action(&mut osłona);
+    dziennik::audyt(nadawca, msg_on_success, &osłona.to_string());
+    if readd {
+        osłony.insert(osłona);
+    }
+
+    Ok(msg_on_success)
+}
+
+fn osłona_dodaj(
+    nadawca: &Nadawca,
+    args: &[Arg],
+    holder: &mut <OsłonyGraczaHolder as TypeMapKey>::Value,
+) -> Result<&'static str, Błąd> {
+    let nazwa = nazwa(args)?;
+    let osłona = zug::Osłona::try_parse(args)?;
+    let osłona_gracza = OsłonaGracza {
+        nazwa: Arc::from(nazwa),
+        założona: false,
+        osłona,
+        id: rand::random(),
+    };
+    let opis = osłona_gracza.to_string();
+    dodaj_osłonę(holder.entry(nadawca.gracz).or_default(), osłona_gracza)?;
+    dziennik::audyt(nadawca, "osłona.dodano", &opis);
+
+    Ok("osłona.dodano")
+}
+
+/// kopia osłony z arsenału gildii, nazwana jak szablon, o ile nie podano innej nazwy.
+async fn osłona_weź(ctx: &Context, nadawca: &Nadawca, args: &[Arg]) -> Result<String, Błąd> {
+    let mowa = ustawienia::mowa(ctx, nadawca).await;
+    let (szablon, osłona) = z_arsenału(ctx, nadawca, args, |szablon| match szablon {
+        Szablon::Osłona(osłona) => Some(osłona.clone()),
+        Szablon::Broń(_) => None,
+    })
+    .await?;
+    let osłona_gracza = OsłonaGracza {
+        nazwa: Arc::from(nazwa(args).unwrap_or(szablon.as_str())),
+        założona: false,
+        osłona,
+        id: rand::random(),
+    };
+    let opis = osłona_gracza.to_string();
+
+    let mut data = ctx.data.write().await;
+    let osłony = data
+        .get_mut::<OsłonyGraczaHolder>()
+        .ok_or_else(|| Błąd::Magazyn("brak OsłonyGraczaHolder w TypeMap".to_owned()))?
+        .entry(nadawca.gracz)
+        .or_default();
+    dodaj_osłonę(osłony, osłona_gracza)?;
+    dziennik::audyt(nadawca, "osłona.wzięto", &opis);
+    zachowaj::<OsłonyGraczaHolder>(&data)?;
+    Ok(mowa.wstaw("osłona.wzięto", &[("szablon", &szablon)]))
+}
+
+#[allow(clippy::significant_drop_tightening)] // compiler errors if sugestion followed
+async fn obsłuż_osłonę(ctx: &Context, nadawca: &Nadawca, args: &[Arg]) -> Result<String, Błąd> {
+    if let Some(Arg::Plain(cmd)) = args.first()
+        && cmd == "weź"
+    {
+        return osłona_weź(ctx, nadawca, args).await;
+    }
+    let mowa = ustawienia::mowa(ctx, nadawca).await;
+    let mut data = ctx.data.write().await;
+    let holder = data
+        .get_mut::<OsłonyGraczaHolder>()
+        .ok_or_else(|| Błąd::Magazyn("brak OsłonyGraczaHolder w TypeMap".to_owned()))?;
+
+    match args.first() {
+        Some(Arg::Plain(cmd)) => {
+            let klucz = match cmd.as_str() {
+                "dodaj" => osłona_dodaj(nadawca, args, holder)?,
+                "załóż" => osłona_helper(
+                    nadawca,
+                    args,
+                    holder,
+                    |osłona| osłona.założona = true,
+                    "osłona.założono",
+                    true,
+                )?,
+                "zdejmij" => osłona_helper(
+                    nadawca,
+                    args,
+                    holder,
+                    |osłona| osłona.założona = false,
+                    "osłona.zdjęto",
+                    true,
+                )?,
+                "usuń" => osłona_helper(nadawca, args, holder, |_| {}, "osłona.usunięto", false)?,
+                _ => return Err(Błąd::Argument(InvalidArgument::new(cmd.clone()))),
+            };
+            zachowaj::<OsłonyGraczaHolder>(&data)?;
+            Ok(mowa.tekst(klucz))
+        }
+        Some(_) => Err(Błąd::BrakArgumentu("podkomenda")),
+        None => {
+            let osłony = holder
+                .get(&nadawca.gracz)
+                .filter(|osłony| !osłony.is_empty())
+                .ok_or(Błąd::BrakOsłon)?;
+            Ok(osłony
+                .iter()
+                .sorted_by_key(|osłona| (osłona.założona, osłona.nazwa.clone()))
+                .map(|osłona_gracza| format!("```\n{}\n```", osłona_gracza.opis(&mowa)))
+                .join("\n"))
+        }
+    }
+}
+
 #[command]
+async fn osłona(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let odpowiedź = obsłuż_osłonę(ctx, &Nadawca::from(msg), &argumenty(args)?).await?;
+    msg.reply(ctx, odpowiedź).await?;
+
+    Ok(())
+}
+
+/* ## próba */
+
+/// pomocnicy w próbie wraz z nazwami i wzorcami: gracze wymienieni wzmianką oraz zgłoszeni
+/// komendą `pomagam` w ciągu [`OKNO_POMOCY`]. zgłoszenia zużywa dopiero [`zużyj_pomoc`].
+/// pomocnik bez wzorca znanego regułom gildii pomaga wzorcem rzucającego.
+async fn pomocnicy(
+    ctx: &Context,
+    nadawca: &Nadawca,
+    reguły: &Reguły,
+    wzorzec: &zug::Wzorzec,
+    args: &[Arg],
+) -> Vec<(String, zug::Wzorzec)> {
+    let mut gracze = wzmianki(args);
+    let data = ctx.data.read().await;
+    if let Some(holder) = data.get::<PomocHolder>() {
+        gracze.extend(
+            holder
+                .get(&(nadawca.kanał, nadawca.gracz))
+                .cloned()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|(_, kiedy)| kiedy.elapsed() < OKNO_POMOCY)
+                .map(|(gracz, _)| gracz),
+        );
+    }
+    let wzorce = gracze
+        .into_iter()
+        .filter(|gracz| *gracz != nadawca.gracz)
+        .unique()
+        .map(|gracz| {
+            let wzorzec = data
+                .get::<WzorzecHolder>()
+                .and_then(|holder| holder.get(&gracz))
+                .filter(|wzorzec| wzorzec.kość(reguły).is_ok())
+                .unwrap_or(wzorzec)
+                .clone();
+            (gracz, wzorzec)
+        })
+        .collect::<Vec<_>>();
+    drop(data);
+
+    let mut pomocnicy = Vec::new();
+    for (gracz, wzorzec) in wzorce {
+        let pomocnik = Nadawca { gracz, ..*nadawca };
+        let nazwa = ustawienia::postać(ctx, &pomocnik)
+            .await
+            .unwrap_or_else(|| gracz.mention().to_string());
+        pomocnicy.push((nazwa, wzorzec));
+    }
+    pomocnicy
+}
+
+/// usuwa zgłoszenia pomocy w próbie nadawcy, gdy próba się odbyła.
+async fn zużyj_pomoc(ctx: &Context, nadawca: &Nadawca) {
+    let mut data = ctx.data.write().await;
+    if let Some(holder) = data.get_mut::<PomocHolder>() {
+        holder.remove(&(nadawca.kanał, nadawca.gracz));
+    }
+}
+
 #[allow(clippy::significant_drop_tightening)] // compiler errors if sugestion followed
-async fn próba(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    let data = ctx.data.write().await;
+async fn obsłuż_próbę(ctx: &Context, nadawca: &Nadawca, args: &[Arg]) -> Result<Odpowiedź, Błąd> {
+    let data = ctx.data.read().await;
     let holder = data
         .get::<WzorzecHolder>()
-        .expect("spodziewano się WzorzecHolder w TypeMap.");
-
-    if let Some(wzorzec) = holder.get(&msg.author.id) {
-        let args = Arg::try_parse(args)?;
-        msg.reply(
-            ctx,
-            zug::próba(
-                *wzorzec,
-                args.iter()
-                    .filter_map(zug::Fach::try_parse)
-                    .last()
-                    .unwrap_or_default(),
-                args.iter().filter_map(zug::Narzędzie::try_parse).last(),
-            ),
-        )
-        .await?;
-    } else {
-        msg.reply(ctx, "nie posiadasz prawzoru.").await?;
+        .ok_or_else(|| Błąd::Magazyn("brak WzorzecHolder w TypeMap".to_owned()))?;
+
+    let wzorzec = holder.get(&nadawca.gracz).ok_or(Błąd::BrakWzorca)?.clone();
+    drop(data);
+    let reguły = ustawienia::reguły(ctx, nadawca).await;
+    let pomocnicy = pomocnicy(ctx, nadawca, &reguły, &wzorzec, args).await;
+    let wynik = zug::próba(
+        &reguły,
+        &wzorzec,
+        args.iter()
+            .filter_map(zug::Fach::try_parse)
+            .next_back()
+            .unwrap_or_default(),
+        &args
+            .iter()
+            .filter_map(zug::Narzędzie::try_parse)
+            .collect::<Vec<_>>(),
+        pomocnicy.iter().map(|(nazwa, wzorzec)| (nazwa.as_str(), wzorzec)),
+        modyfikator(args, &komendy::PREMIA, &komendy::KARA),
+    )?;
+    zużyj_pomoc(ctx, nadawca).await;
+    zapamiętaj_rzut(ctx, nadawca, &wynik).await?;
+    Ok(wyniki::odpowiedź(ctx, nadawca, &wynik).await)
+}
+
+#[command]
+async fn próba(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let args = argumenty(args)?;
+    let nadawca = Nadawca::from(msg);
+    let ukryty = ukryty(ctx, &nadawca, &args).await?;
+    let wynik = obsłuż_próbę(ctx, &nadawca, &args).await?;
+    ogłoś_wynik(ctx, msg, "próba", &args, ukryty, wynik).await
+}
+
+/* ## pomagam */
+
+/// zgłasza nadawcę do pomocy w najbliższej próbie wskazanego gracza na tym samym kanale.
+async fn obsłuż_pomoc(ctx: &Context, nadawca: &Nadawca, args: &[Arg]) -> Result<String, Błąd> {
+    let mowa = ustawienia::mowa(ctx, nadawca).await;
+    let rzucający = wzmianki(args)
+        .into_iter()
+        .next()
+        .ok_or(Błąd::BrakArgumentu("gracz"))?;
+    if rzucający == nadawca.gracz {
+        return Err(Błąd::NiepoprawnaWartość("gracz", rzucający.mention().to_string()));
     }
 
+    let mut data = ctx.data.write().await;
+    let holder = data
+        .get_mut::<PomocHolder>()
+        .ok_or_else(|| Błąd::Magazyn("brak PomocHolder w TypeMap".to_owned()))?;
+    // przeterminowane zgłoszenia usuwane są przy każdym nowym
+    holder.retain(|_, zgłoszeni| {
+        zgłoszeni.retain(|(_, kiedy)| kiedy.elapsed() < OKNO_POMOCY);
+        !zgłoszeni.is_empty()
+    });
+    let zgłoszeni = holder.entry((nadawca.kanał, rzucający)).or_default();
+    zgłoszeni.retain(|(pomocnik, _)| *pomocnik != nadawca.gracz);
+    zgłoszeni.push((nadawca.gracz, Instant::now()));
+    drop(data);
+
+    let minuty = usize::try_from(OKNO_POMOCY.as_secs() / 60).unwrap_or_default();
+    Ok(mowa.wstaw(
+        "pomagam.zgłoszono",
+        &[
+            ("rzucający", &rzucający.mention().to_string()),
+            ("czas", &mowa.liczebnik("pomagam.minuta", minuty)),
+        ],
+    ))
+}
+
+#[command]
+async fn pomagam(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let odpowiedź = obsłuż_pomoc(ctx, &Nadawca::from(msg), &argumenty(args)?).await?;
+    msg.reply(ctx, odpowiedź).await?;
+
     Ok(())
 }
 
 /* ## bitwa */
 
-#[command]
+/// bronie gracza użyte w bitwie: z zestawu wskazanego opcją albo aktywne. muszą mieścić się
+/// w uzbrojeniu dozwolonym przez reguły.
+fn bronie_bitwy<'a>(
+    data: &'a TypeMap,
+    nadawca: &Nadawca,
+    reguły: &Reguły,
+    args: &[Arg],
+) -> Result<Vec<&'a BrońGracza>, Błąd> {
+    let holder = data
+        .get::<BronieGraczaHolder>()
+        .ok_or_else(|| Błąd::Magazyn("brak BronieGraczaHolder w TypeMap".to_owned()))?;
+    let Some(bronie) = holder.get(&nadawca.gracz) else {
+        return Ok(Vec::new());
+    };
+    // zestaw zastępuje bronie aktywne
+    let zestaw = args
+        .iter()
+        .filter_map(|arg| komendy::ZESTAW.dopasuj(arg))
+        .filter_map(<[String]>::last)
+        .next_back()
+        .map(|zestaw| {
+            data.get::<ZestawyGraczaHolder>()
+                .and_then(|holder| holder.get(&nadawca.gracz))
+                .and_then(|zestawy| zestawy.get(zestaw))
+                .ok_or_else(|| Błąd::NieznanyZestaw(zestaw.clone()))
+        })
+        .transpose()?;
+    let użyte = bronie
+        .iter()
+        .filter(|broń| zestaw.map_or(broń.aktywna, |ids| ids.contains(&broń.id)))
+        .collect::<Vec<_>>();
+    zug::sprawdź_uzbrojenie(reguły, użyte.iter().map(|broń| &broń.broń))?;
+    Ok(użyte)
+}
+
 #[allow(clippy::significant_drop_tightening)] // compiler errors if sugestion followed
-async fn bitwa(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    let data = ctx.data.write().await;
+async fn obsłuż_bitwę(ctx: &Context, nadawca: &Nadawca, args: &[Arg]) -> Result<Odpowiedź, Błąd> {
+    let reguły = ustawienia::reguły(ctx, nadawca).await;
+    let data = ctx.data.read().await;
     let holder_wzorzec = data
         .get::<WzorzecHolder>()
-        .expect("spodziewano się WzorzecHolder w TypeMap.");
-    let holder_broń = data
-        .get::<BronieGraczaHolder>()
-        .expect("spodziewano się WzorzecHolder w TypeMap.");
+        .ok_or_else(|| Błąd::Magazyn("brak WzorzecHolder w TypeMap".to_owned()))?;
+    let holder_osłona = data
+        .get::<OsłonyGraczaHolder>()
+        .ok_or_else(|| Błąd::Magazyn("brak OsłonyGraczaHolder w TypeMap".to_owned()))?;
 
-    if let Some(wzorzec) = holder_wzorzec.get(&msg.author.id) {
-        let args = Arg::try_parse(args)?;
-        let mod_pos = args
-            .iter()
-            .filter_map(|arg| match arg {
-                Arg::Short('p', options) => Some(options),
-                Arg::Long(param, options) => {
-                    (dist(param, "plus") < 3).then_some(options)
-                }
-                _ => None,
-            })
-            .filter_map(|options| options.last()?.parse::<isize>().ok())
-            .sum::<isize>();
-        let mod_neg = args
+    let wzorzec = holder_wzorzec.get(&nadawca.gracz).ok_or(Błąd::BrakWzorca)?;
+    let osłony = OsłonyGracza::new();
+    let użyte = bronie_bitwy(&data, nadawca, &reguły, args)?;
+    let wynik = zug::bitwa(
+        &reguły,
+        wzorzec,
+        użyte.iter().map(|broń| (broń.nazwa.as_ref(), &broń.broń)),
+        holder_osłona
+            .get(&nadawca.gracz)
+            .unwrap_or(&osłony)
             .iter()
-            .filter_map(|arg| match arg {
-                Arg::Short('m', options) => Some(options),
-                Arg::Long(param, options) => {
-                    if dist(param, "minus") < 3 {
-                        Some(options)
-                    } else {
-                        None
-                    }
-                }
-                _ => None,
-            })
-            .filter_map(|options| options.last()?.parse::<isize>().ok())
-            .sum::<isize>();
-
-        let kurwa = BronieGracza::new(); // necessary to make the next line work
-        msg.reply(
-            ctx,
-            zug::bitwa(
-                *wzorzec,
-                holder_broń
-                    .get(&msg.author.id)
-                    .unwrap_or(&kurwa)
-                    .iter()
-                    .filter(|broń| broń.aktywna)
-                    .map(|broń| broń.broń),
-                mod_pos - mod_neg,
-            ),
-        )
-        .await?;
-    } else {
-        msg.reply(ctx, "nie posiadasz prawzoru.").await?;
+            .filter(|osłona| osłona.założona)
+            .map(|osłona| &osłona.osłona),
+        modyfikator(args, &komendy::PLUS, &komendy::MINUS),
+    )?;
+    drop(data);
+    zapamiętaj_rzut(ctx, nadawca, &wynik).await?;
+    Ok(wyniki::odpowiedź(ctx, nadawca, &wynik).await)
+}
+
+#[command]
+async fn bitwa(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let args = argumenty(args)?;
+    let nadawca = Nadawca::from(msg);
+    let ukryty = ukryty(ctx, &nadawca, &args).await?;
+    let wynik = obsłuż_bitwę(ctx, &nadawca, &args).await?;
+    ogłoś_wynik(ctx, msg, "bitwa", &args, ukryty, wynik).await
+}
+
+/* ## przerzuć */
+
+/// zapisuje próbę lub bitwę jako ostatni rzut gracza, który można przerzucić.
+async fn zapamiętaj_rzut(
+    ctx: &Context,
+    nadawca: &Nadawca,
+    wynik: &zug::Wynik,
+) -> Result<(), Błąd> {
+    let mut data = ctx.data.write().await;
+    data.get_mut::<OstatnieRzutyHolder>()
+        .ok_or_else(|| Błąd::Magazyn("brak OstatnieRzutyHolder w TypeMap".to_owned()))?
+        .insert(nadawca.gracz, wynik.clone());
+    zachowaj::<OstatnieRzutyHolder>(&data)
+}
+
+/// numery kości podane argumentami, także razem, jak w poleceniu `kości:2 5`.
+fn numery_kości(args: &[Arg]) -> Result<Vec<usize>, Błąd> {
+    args.iter()
+        .filter_map(|arg| match arg {
+            Arg::Plain(numery) => Some(numery.split_whitespace()),
+            _ => None,
+        })
+        .flatten()
+        .map(|numer| {
+            numer
+                .parse::<usize>()
+                .map_err(|_| Błąd::NiepoprawnaWartość("kość", numer.to_owned()))
+        })
+        .collect()
+}
+
+#[allow(clippy::significant_drop_tightening)] // compiler errors if sugestion followed
+async fn obsłuż_przerzut(
+    ctx: &Context,
+    nadawca: &Nadawca,
+    args: &[Arg],
+) -> Result<Odpowiedź, Błąd> {
+    let mut numery = numery_kości(args)?;
+    let księżyce = args.iter().any(|arg| komendy::KSIĘŻYCE.pasuje(arg));
+
+    let mut data = ctx.data.write().await;
+    let wynik = data
+        .get_mut::<OstatnieRzutyHolder>()
+        .ok_or_else(|| Błąd::Magazyn("brak OstatnieRzutyHolder w TypeMap".to_owned()))?
+        .get_mut(&nadawca.gracz)
+        .ok_or(Błąd::BrakRzutu)?;
+    if księżyce {
+        numery.extend(wynik.numery(zug::Znak::Księżyc));
     }
+    numery.sort_unstable();
+    numery.dedup();
+    if numery.is_empty() {
+        return Err(if księżyce {
+            Błąd::Niedozwolone("przerzut")
+        } else {
+            Błąd::BrakArgumentu("kość")
+        });
+    }
+    let przerzut = wynik
+        .przerzuć(&numery)?
+        .iter()
+        .map(|przerzut| format!("{}: {:?} -> {:?}", przerzut.numer, przerzut.przed, przerzut.po))
+        .join(", ");
+    let wynik = wynik.clone();
+    dziennik::audyt(nadawca, "rzut.przerzucono", &format!("{}: {przerzut}", wynik.rzut));
+    zachowaj::<OstatnieRzutyHolder>(&data)?;
+    drop(data);
+    Ok(wyniki::przerzut(ctx, nadawca, &wynik).await)
+}
+
+#[command]
+async fn przerzuć(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let args = argumenty(args)?;
+    let nadawca = Nadawca::from(msg);
+    let ukryty = ukryty(ctx, &nadawca, &args).await?;
+    let wynik = obsłuż_przerzut(ctx, &nadawca, &args).await?;
+    ogłoś_wynik(ctx, msg, "przerzuć", &args, ukryty, wynik).await
+}
+
+/* ## pula */
+
+/// kości, którymi rzuciłaby próba lub bitwa z tymi samymi argumentami. niczym nie rzuca.
+#[allow(clippy::significant_drop_tightening)] // compiler errors if sugestion followed
+async fn obsłuż_pulę(ctx: &Context, nadawca: &Nadawca, args: &[Arg]) -> Result<String, Błąd> {
+    let mowa = ustawienia::mowa(ctx, nadawca).await;
+    let reguły = ustawienia::reguły(ctx, nadawca).await;
+    let data = ctx.data.read().await;
+    let wzorzec = data
+        .get::<WzorzecHolder>()
+        .ok_or_else(|| Błąd::Magazyn("brak WzorzecHolder w TypeMap".to_owned()))?
+        .get(&nadawca.gracz)
+        .ok_or(Błąd::BrakWzorca)?
+        .clone();
+    drop(data);
+    // pomocnicy dokładają kości tylko do próby
+    let pomocnicy = if matches!(args.first(), Some(Arg::Plain(rzut)) if rzut == "próba") {
+        pomocnicy(ctx, nadawca, &reguły, &wzorzec, args).await
+    } else {
+        Vec::new()
+    };
+
+    let data = ctx.data.read().await;
+    let pula = match args.first() {
+        Some(Arg::Plain(rzut)) if rzut == "próba" => zug::pula_próby(
+            &reguły,
+            &wzorzec,
+            args.iter()
+                .filter_map(zug::Fach::try_parse)
+                .next_back()
+                .unwrap_or_default(),
+            &args
+                .iter()
+                .filter_map(zug::Narzędzie::try_parse)
+                .collect::<Vec<_>>(),
+            pomocnicy.iter().map(|(nazwa, wzorzec)| (nazwa.as_str(), wzorzec)),
+            modyfikator(args, &komendy::PREMIA, &komendy::KARA),
+        )?,
+        Some(Arg::Plain(rzut)) if rzut == "bitwa" => zug::pula_bitwy(
+            &reguły,
+            &wzorzec,
+            bronie_bitwy(&data, nadawca, &reguły, args)?
+                .into_iter()
+                .map(|broń| (broń.nazwa.as_ref(), &broń.broń)),
+            modyfikator(args, &komendy::PLUS, &komendy::MINUS),
+        )?,
+        _ => return Err(Błąd::BrakArgumentu("podkomenda")),
+    };
+    let razem = mowa.liczebnik("pula.kość", pula.iter().map(|kości| kości.ile).sum());
+    let mut linie = pula
+        .iter()
+        .map(|kości| {
+            let klucz = match kości.rodzaj {
+                zug::RodzajKości::Wzorzec => "pula.wzorzec",
+                zug::RodzajKości::Narzędzie => "pula.narzędzie",
+                zug::RodzajKości::Broń => "pula.broń",
+                zug::RodzajKości::Pomoc => "pula.pomoc",
+            };
+            let jakość = kości
+                .narzędzie
+                .map(|narzędzie| mowa.tekst(&format!("jakość.{}", narzędzie.nazwa())))
+                .unwrap_or_default();
+            let liczba = mowa.liczebnik("pula.kość", kości.ile);
+            mowa.wstaw(
+                klucz,
+                &[("nazwa", &kości.nazwa), ("jakość", &jakość), ("kości", &liczba)],
+            )
+        })
+        .collect::<Vec<_>>();
+    linie.push(mowa.wstaw("pula.razem", &[("kości", &razem)]));
+    Ok(linie.join("\n"))
+}
+
+#[command]
+async fn pula(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let odpowiedź = obsłuż_pulę(ctx, &Nadawca::from(msg), &argumenty(args)?).await?;
+    msg.reply(ctx, odpowiedź).await?;
 
     Ok(())
 }
 
 /* ## zanik */
 
+#[allow(clippy::match_bool)] // i think this is more readable
+fn zanik_sprzętu(mowa: &Mowa, reguły: &Reguły, args: &[Arg]) -> Result<String, Błąd> {
+    let trwałość = match args.first() {
+        Some(Arg::Plain(trwałość_str)) => trwałość_str
+            .parse::<usize>()
+            .map_err(|_| Błąd::NiepoprawnaWartość("trwałość", trwałość_str.clone()))?,
+        _ => return Err(Błąd::BrakArgumentu("trwałość")),
+    };
+    let jakość = args
+        .iter()
+        .filter_map(zug::Narzędzie::try_parse)
+        .next_back()
+        .unwrap_or_default();
+    let (klucz, wydźwięk) = match zug::zanik(reguły, trwałość, jakość)? {
+        true => ("zanik.porażka", "zniszczenie"),
+        false => ("zanik.sukces", "trwałość"),
+    };
+    let trwałość = trwałość.to_string();
+    let jakość = mowa.tekst(&format!("jakość.{}", jakość.nazwa()));
+    let wartości = [("trwałość", trwałość.as_str()), ("jakość", jakość.as_str())];
+    Ok(wyniki::z_opisem(
+        mowa,
+        mowa.wstaw(klucz, &wartości),
+        wydźwięk,
+        &wartości,
+    ))
+}
+
+/// zanik osłony gracza według jej trwałości i jakości. utracona trwałość jest zapisywana,
+/// a osłona, która straci całą, ulega zniszczeniu.
+#[allow(clippy::significant_drop_tightening)] // compiler errors if sugestion followed
+async fn zanik_osłony(
+    ctx: &Context,
+    nadawca: &Nadawca,
+    mowa: &Mowa,
+    reguły: &Reguły,
+    nazwa: &str,
+) -> Result<String, Błąd> {
+    let mut data = ctx.data.write().await;
+    let holder = data
+        .get_mut::<OsłonyGraczaHolder>()
+        .ok_or_else(|| Błąd::Magazyn("brak OsłonyGraczaHolder w TypeMap".to_owned()))?;
+    let osłony = holder.get_mut(&nadawca.gracz).ok_or(Błąd::BrakOsłon)?;
+    let mut osłona = wyjmij_osłonę(osłony, nazwa)?;
+    let utracona = match zug::zanik(reguły, osłona.osłona.trwałość, osłona.osłona.jakość) {
+        Ok(utracona) => utracona,
+        Err(why) => {
+            osłony.insert(osłona);
+            return Err(why);
+        }
+    };
+
+    let (klucz, wydźwięk) = if utracona {
+        ("zanik.porażka", "zniszczenie")
+    } else {
+        ("zanik.sukces", "trwałość")
+    };
+    let trwałość = osłona.osłona.trwałość.to_string();
+    let jakość = mowa.tekst(&format!("jakość.{}", osłona.osłona.jakość.nazwa()));
+    let wartości = [("trwałość", trwałość.as_str()), ("jakość", jakość.as_str())];
+    let mut treść = mowa.wstaw(klucz, &wartości);
+    if utracona {
+        osłona.osłona.trwałość -= 1;
+        let stan = match osłona.osłona.trwałość {
+            0 => "osłona.zniszczona",
+            _ => "osłona.osłabiona",
+        };
+        let pozostała = osłona.osłona.trwałość.to_string();
+        treść.push('\n');
+        treść.push_str(&mowa.wstaw(stan, &[("nazwa", &osłona.nazwa), ("trwałość", &pozostała)]));
+    }
+    dziennik::audyt(nadawca, "zanik", &osłona.to_string());
+    if osłona.osłona.trwałość > 0 {
+        osłony.insert(osłona);
+    }
+    zachowaj::<OsłonyGraczaHolder>(&data)?;
+
+    Ok(wyniki::z_opisem(mowa, treść, wydźwięk, &wartości))
+}
+
+/// zanik osłony wskazanej nazwą lub sprzętu o podanej trwałości.
+async fn obsłuż_zanik(ctx: &Context, nadawca: &Nadawca, args: &[Arg]) -> Result<String, Błąd> {
+    let mowa = ustawienia::mowa(ctx, nadawca).await;
+    let reguły = ustawienia::reguły(ctx, nadawca).await;
+    if args.iter().any(|arg| komendy::NAZWA.pasuje(arg)) {
+        zanik_osłony(ctx, nadawca, &mowa, &reguły, nazwa(args)?).await
+    } else {
+        zanik_sprzętu(&mowa, &reguły, args)
+    }
+}
+
 #[command]
 async fn zanik(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    let args = Arg::try_parse(args)?;
-    if let Some(arg) = args.first() {
-        if let Arg::Plain(trwałość_str) = arg && let Ok(trwałość) = trwałość_str.parse::<usize>() {
-            if let Some(jakość) = args.last().map(zug::Narzędzie::try_parse).unwrap_or_default()  {
-                if let Ok(message) =zug::zanik(trwałość, jakość) { 
-                msg.reply(ctx, message).await?;
+    let args = argumenty(args)?;
+    let nadawca = Nadawca::from(msg);
+    let ukryty = ukryty(ctx, &nadawca, &args).await?;
+    let wynik = Odpowiedź::from(obsłuż_zanik(ctx, &nadawca, &args).await?);
+    ogłoś_wynik(ctx, msg, "zanik", &args, ukryty, wynik).await
+}
+
+/* ## ustawienia */
+
+fn niepoprawne(ustawienie: &'static str, wartości: &[Arg]) -> Błąd {
+    Błąd::NiepoprawnaWartość(ustawienie, wartości.iter().join(" "))
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions("ADMINISTRATOR")]
+#[allow(clippy::significant_drop_tightening)] // compiler errors if sugestion followed
+async fn ustawienia(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let gildia = msg.guild_id.ok_or(Błąd::Niedozwolone("gildia"))?;
+    let nadawca = Nadawca::from(msg);
+    let preferowany = ustawienia::gracza(ctx, &nadawca).await.język;
+    let mowa = ustawienia::mowa(ctx, &nadawca).await;
+    let zestawy = ctx
+        .data
+        .read()
+        .await
+        .get::<RegułyHolder>()
+        .map(|zestawy| zestawy.nazwane.keys().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+    // prefiksy, wzmianki, emoji oraz szablony wymagają wielkości liter,
+    // której parser nie zachowuje
+    let surowe = args.raw().map(str::to_owned).collect::<Vec<_>>();
+    let tekst_surowy = surowe.get(2..).unwrap_or_default().join(" ");
+    let args = match surowe.first().map(|klucz| klucz.to_lowercase()) {
+        // szablony, opisy oraz ścianki wzorców są dowolnym tekstem, którego parser nie rozumie
+        Some(klucz) if ["szablon", "opis", "wzorzec"].contains(&klucz.as_str()) => {
+            vec![Arg::Plain(klucz)]
+        }
+        _ => argumenty(args)?,
+    };
+
+    let mut data = ctx.data.write().await;
+    let holder = data
+        .get_mut::<UstawieniaHolder>()
+        .ok_or_else(|| Błąd::Magazyn("brak UstawieniaHolder w TypeMap".to_owned()))?;
+    let ustawienia = holder.entry(gildia).or_default();
+    let tekst = |klucz: &str| mowa.tekst(&format!("zmiana.{klucz}"));
+
+    let zmiana = match args.as_slice() {
+        [] => {
+            let opis = format!("```\n{}\n```", ustawienia.opis(mowa.język));
+            drop(data);
+            msg.reply(ctx, opis).await?;
+            return Ok(());
+        }
+        [Arg::Plain(klucz), wartości @ ..] => match klucz.as_str() {
+            "prefiks" => {
+                let nowy = surowe.get(1..).unwrap_or_default().join(" ");
+                if nowy.chars().any(char::is_whitespace) || nowy.chars().count() > LIMIT_PREFIKSU {
+                    return Err(Błąd::NiepoprawnaWartość("prefiks", nowy).into());
+                }
+                // bez wartości gildia wraca do prefiksów z konfiguracji
+                ustawienia.prefiks = Some(nowy).filter(|nowy| !nowy.is_empty());
+                let prefiks = ustawienia
+                    .prefiks
+                    .clone()
+                    .unwrap_or_else(|| mowa.tekst("ustawienia.domyślny"));
+                mowa.wstaw("zmiana.prefiks", &[("prefiks", &prefiks)])
+            }
+            "język" => {
+                ustawienia.język = match wartości {
+                    [Arg::Plain(wybrany)] => Język::try_parse(wybrany)
+                        .ok_or_else(|| niepoprawne("język", wartości))?,
+                    _ => return Err(niepoprawne("język", wartości).into()),
+                };
+                // potwierdzenie już w nowym języku, o ile gracz nie wybrał własnego
+                let mowa = Mowa {
+                    język: preferowany.unwrap_or(ustawienia.język),
+                    ..mowa.clone()
+                };
+                mowa.wstaw(
+                    "zmiana.język",
+                    &[("język", &ustawienia.język.nazwa(mowa.język))],
+                )
+            }
+            "glify" => {
+                ustawienia.glify = match (wartości, surowe.get(2), surowe.get(3)) {
+                    ([Arg::Plain(styl)], _, _) if styl == "unicode" => Glify::Unicode,
+                    ([Arg::Plain(styl)], _, _) if styl == "ascii" => Glify::Ascii,
+                    ([Arg::Plain(styl), _, _], Some(słońce), Some(księżyc)) if styl == "własne" => {
+                        Glify::Własne {
+                            słońce: słońce.clone(),
+                            księżyc: księżyc.clone(),
+                        }
+                    }
+                    _ => return Err(niepoprawne("glify", wartości).into()),
+                };
+                mowa.wstaw("zmiana.glify", &[("glify", &ustawienia.glify.to_string())])
+            }
+            "mg" => {
+                ustawienia.rola_mg = match (wartości, surowe.get(1)) {
+                    ([Arg::Plain(brak)], _) if brak == "brak" => None,
+                    (_, Some(wzmianka)) => Some(RoleId(
+                        parse_role(wzmianka).ok_or_else(|| niepoprawne("rola", wartości))?,
+                    )),
+                    _ => return Err(niepoprawne("rola", wartości).into()),
+                };
+                tekst("mg")
+            }
+            "kanały" => {
+                let kanały = surowe
+                    .iter()
+                    .skip(2)
+                    .map(|wzmianka| parse_channel(wzmianka).map(ChannelId))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| niepoprawne("kanały", wartości))?;
+                match wartości.first() {
+                    Some(Arg::Plain(akcja)) if akcja == "dodaj" => {
+                        ustawienia.kanały.extend(kanały);
+                    }
+                    Some(Arg::Plain(akcja)) if akcja == "usuń" => {
+                        ustawienia.kanały.retain(|kanał| !kanały.contains(kanał));
+                    }
+                    Some(Arg::Plain(akcja)) if akcja == "wszystkie" => ustawienia.kanały.clear(),
+                    _ => return Err(niepoprawne("kanały", wartości).into()),
+                }
+                tekst("kanały")
+            }
+            "ukryte" => {
+                ustawienia.ukryte_rzuty = match wartości {
+                    [Arg::Plain(wartość)] if wartość == "tak" => true,
+                    [Arg::Plain(wartość)] if wartość == "nie" => false,
+                    _ => return Err(niepoprawne("ukryte", wartości).into()),
+                };
+                tekst("ukryte")
+            }
+            "obrazy" => {
+                ustawienia.obrazy = match wartości {
+                    [Arg::Plain(wartość)] if wartość == "tak" => true,
+                    [Arg::Plain(wartość)] if wartość == "nie" => false,
+                    _ => return Err(niepoprawne("obrazy", wartości).into()),
+                };
+                tekst("obrazy")
+            }
+            "reguły" => {
+                ustawienia.reguły = match wartości {
+                    [] => None,
+                    [Arg::Plain(zestaw)] if zestawy.contains(zestaw) => Some(zestaw.clone()),
+                    _ => return Err(niepoprawne("reguły", wartości).into()),
+                };
+                let reguły = ustawienia
+                    .reguły
+                    .clone()
+                    .unwrap_or_else(|| mowa.tekst("ustawienia.podstawowe"));
+                mowa.wstaw("zmiana.reguły", &[("reguły", &reguły)])
+            }
+            "wzorzec" => {
+                let nazwa = surowe
+                    .get(1)
+                    .map(|nazwa| nazwa.to_lowercase())
+                    .ok_or(Błąd::BrakArgumentu("wzorzec"))?;
+                let reszta = surowe.get(2..).unwrap_or_default();
+                if reszta.is_empty() {
+                    ustawienia.wzorce.remove(&nazwa);
+                    mowa.wstaw("zmiana.wzorzec_usunięty", &[("wzorzec", &nazwa)])
                 } else {
-                msg.reply(ctx, format!("podano niepoprawną jakość. {}", KRZYCZ)).await?;
+                    let (ścianki, aliasy) = reszta.split_at(reszta.len().min(ŚCIANKI_WZORCA));
+                    let wzorzec = WłasnyWzorzec {
+                        kość: Kość::try_from(ścianki.to_vec())
+                            .map_err(|why| Błąd::NiepoprawnaWartość("wzorzec", why))?,
+                        aliasy: aliasy.iter().map(|alias| alias.to_lowercase()).collect(),
+                    };
+                    wzorzec
+                        .sprawdź(&nazwa)
+                        .map_err(|why| Błąd::NiepoprawnaWartość("wzorzec", why))?;
+                    let zmiana = mowa.wstaw("zmiana.wzorzec", &[("wzorzec", &nazwa)]);
+                    ustawienia.wzorce.insert(nazwa, wzorzec);
+                    zmiana
                 }
-            } else {
-                msg.reply(ctx, format!("podano niepoprawną jakość. {}", KRZYCZ)).await?;
             }
-        } else {
-            msg.reply(ctx, format!("podano niepoprawną trwałość. {}", KRZYCZ)).await?;
+            "szablon" => {
+                let szablon = surowe
+                    .get(1)
+                    .map(|szablon| szablon.to_lowercase())
+                    .ok_or(Błąd::BrakArgumentu("szablon"))?;
+                // szablonem można zastąpić wyłącznie tekst istniejący w katalogu
+                if teksty::znajdź(Język::Polski, &szablon).is_none() {
+                    return Err(Błąd::NiepoprawnaWartość("szablon", szablon).into());
+                }
+                if tekst_surowy.is_empty() {
+                    ustawienia.szablony.remove(&szablon);
+                    mowa.wstaw("zmiana.szablon_usunięty", &[("klucz", &szablon)])
+                } else {
+                    ustawienia.szablony.insert(szablon.clone(), tekst_surowy);
+                    mowa.wstaw("zmiana.szablon", &[("klucz", &szablon)])
+                }
+            }
+            "opis" => {
+                let wydźwięk = surowe
+                    .get(1)
+                    .map(|wydźwięk| wydźwięk.to_lowercase())
+                    .ok_or(Błąd::BrakArgumentu("opis"))?;
+                if !wyniki::WYDŹWIĘKI.contains(&wydźwięk.as_str()) {
+                    return Err(Błąd::NiepoprawnaWartość("opis", wydźwięk).into());
+                }
+                if tekst_surowy.is_empty() {
+                    ustawienia.opisy.remove(&wydźwięk);
+                    mowa.wstaw("zmiana.opisy_usunięte", &[("wydźwięk", &wydźwięk)])
+                } else {
+                    let zmiana = mowa.wstaw("zmiana.opis", &[("wydźwięk", &wydźwięk)]);
+                    ustawienia.opisy.entry(wydźwięk).or_default().push(tekst_surowy);
+                    zmiana
+                }
+            }
+            _ => return Err(Błąd::Argument(InvalidArgument::new(klucz.clone())).into()),
+        },
+        _ => return Err(Błąd::BrakArgumentu("ustawienie").into()),
+    };
+
+    dziennik::audyt(&nadawca, "ustawienia", &zmiana);
+    zachowaj::<UstawieniaHolder>(&data)?;
+    drop(data);
+    msg.reply(ctx, zmiana).await?;
+
+    Ok(())
+}
+
+/* ## preferencje */
+
+#[command]
+#[allow(clippy::significant_drop_tightening)] // compiler errors if sugestion followed
+async fn preferencje(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let args = argumenty(args)?;
+    let nadawca = Nadawca::from(msg);
+    let język_gildii = ustawienia::gildii(ctx, &nadawca).await.język;
+    let mowa = ustawienia::mowa(ctx, &nadawca).await;
+
+    let mut data = ctx.data.write().await;
+    let holder = data
+        .get_mut::<UstawieniaGraczaHolder>()
+        .ok_or_else(|| Błąd::Magazyn("brak UstawieniaGraczaHolder w TypeMap".to_owned()))?;
+    let preferencje = holder.entry(msg.author.id).or_default();
+
+    let zmiana = match args.as_slice() {
+        [] => {
+            let opis = format!("```\n{}\n```", preferencje.opis(mowa.język));
+            drop(data);
+            msg.reply(ctx, opis).await?;
+            return Ok(());
         }
-    } else {
-        msg.reply(ctx, format!("nie podano trwałości. {}", KRZYCZ))
-            .await?;
-    }
+        [Arg::Plain(klucz), wartości @ ..] => match klucz.as_str() {
+            "opisowe" => {
+                preferencje.opisowe = match wartości {
+                    [Arg::Plain(wartość)] if wartość == "tak" => true,
+                    [Arg::Plain(wartość)] if wartość == "nie" => false,
+                    _ => return Err(niepoprawne("opisowe", wartości).into()),
+                };
+                mowa.tekst("zmiana.opisowe")
+            }
+            "język" => {
+                preferencje.język = match wartości {
+                    [Arg::Plain(wartość)] if wartość == "serwer" => None,
+                    [Arg::Plain(wybrany)] => Some(
+                        Język::try_parse(wybrany).ok_or_else(|| niepoprawne("język", wartości))?,
+                    ),
+                    _ => return Err(niepoprawne("język", wartości).into()),
+                };
+                let mowa = Mowa {
+                    język: preferencje.język.unwrap_or(język_gildii),
+                    ..mowa.clone()
+                };
+                mowa.wstaw("zmiana.język", &[("język", &mowa.język.nazwa(mowa.język))])
+            }
+            _ => return Err(Błąd::Argument(InvalidArgument::new(klucz.clone())).into()),
+        },
+        _ => return Err(Błąd::BrakArgumentu("ustawienie").into()),
+    };
+
+    dziennik::audyt(&nadawca, "preferencje", &zmiana);
+    zachowaj::<UstawieniaGraczaHolder>(&data)?;
+    drop(data);
+    msg.reply(ctx, zmiana).await?;
+
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn osłony() {
+        let osłona = |nazwa: &str| OsłonaGracza {
+            osłona: zug::Osłona::try_parse(&[
+                Arg::Plain("3".to_owned()),
+                Arg::Short('r', vec!["t".to_owned()]),
+            ])
+            .unwrap(),
+            założona: false,
+            nazwa: Arc::from(nazwa),
+            id: rand::random(),
+        };
+        let mut osłony = OsłonyGracza::new();
+        dodaj_osłonę(&mut osłony, osłona("tarcza")).unwrap();
+        dodaj_osłonę(&mut osłony, osłona("tarcze")).unwrap();
+        assert!(matches!(
+            dodaj_osłonę(&mut osłony, osłona("tarcza")),
+            Err(Błąd::ZajętaNazwaOsłony(_))
+        ));
+
+        // nazwa podobna do obu osłon nie wskazuje żadnej, a dokładna wyjmuje tylko jedną
+        assert!(matches!(
+            znajdź_osłonę(&osłony, "tarczy"),
+            Err(Błąd::NiejednoznacznaOsłona(..))
+        ));
+        let tarcza = wyjmij_osłonę(&mut osłony, "tarcza").unwrap();
+        assert_eq!(&*tarcza.nazwa, "tarcza");
+        assert_eq!(osłony.len(), 1);
+        assert_eq!(&*wyjmij_osłonę(&mut osłony, "tarczy").unwrap().nazwa, "tarcze");
+        assert!(osłony.is_empty());
+    }
+
+    #[test]
+    fn bronie() {
+        let broń = |nazwa: &str| BrońGracza {
+            broń: zug::Broń::try_parse(&[
+                Arg::Short('w', vec!["l".to_owned()]),
+                Arg::Short('z', vec!["b".to_owned()]),
+            ])
+            .unwrap(),
+            aktywna: false,
+            nazwa: Arc::from(nazwa),
+            id: rand::random(),
+        };
+        let bronie = [broń("miecz"), broń("mieczyk"), broń("nóż")]
+            .into_iter()
+            .collect::<BronieGracza>();
+        let id = |nazwa: &str| bronie.iter().find(|broń| &*broń.nazwa == nazwa).unwrap().id;
+
+        // dokładna nazwa wygrywa z podobnymi, a literówka wskazuje jedyną podobną broń
+        assert_eq!(znajdź_broń(&bronie, "miecz").unwrap(), id("miecz"));
+        assert_eq!(znajdź_broń(&bronie, "noż").unwrap(), id("nóż"));
+        assert!(matches!(znajdź_broń(&bronie, "topór"), Err(Błąd::NieznanaBroń(_))));
+        match znajdź_broń(&bronie, "mieczy") {
+            Err(Błąd::NiejednoznacznaBroń(nazwa, kandydatki)) => {
+                assert_eq!(nazwa, "mieczy");
+                assert_eq!(kandydatki, ["miecz", "mieczyk"]);
+            }
+            _ => panic!("nazwa podobna do dwóch broni powinna być niejednoznaczna"),
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use crate::zug::{RodzajKości, Wynik, Znak};
+use tiny_skia::{Color, FillRule, Paint, Path, PathBuilder, Pixmap, Rect, Stroke, Transform};
+
+/* obrazy wyników rzutów, dołączane do odpowiedzi w gildiach, które je włączyły */
+
+pub const NAZWA_PLIKU: &str = "rzut.png";
+
+const KOŚĆ: f32 = 64.0;
+const ODSTĘP: f32 = 12.0;
+const TŁO: (u8, u8, u8) = (0x2f, 0x31, 0x36);
+
+struct Styl {
+    ścianka: (u8, u8, u8),
+    krawędź: (u8, u8, u8),
+    znak: (u8, u8, u8),
+}
+
+// kości wzorców są jasne, kości narzędzi i broni ciemne, by odróżniały się na pierwszy rzut oka
+const fn styl(rodzaj: RodzajKości) -> Styl {
+    match rodzaj {
+        RodzajKości::Wzorzec | RodzajKości::Pomoc => Styl {
+            ścianka: (0xf2, 0xe8, 0xcf),
+            krawędź: (0x3d, 0x3d, 0x3d),
+            znak: (0x2b, 0x2b, 0x2b),
+        },
+        RodzajKości::Narzędzie | RodzajKości::Broń => Styl {
+            ścianka: (0x6b, 0x4f, 0x3a),
+            krawędź: (0xd9, 0xb3, 0x82),
+            znak: (0xf2, 0xe8, 0xcf),
+        },
+    }
+}
+
+fn farba((r, g, b): (u8, u8, u8)) -> Paint<'static> {
+    let mut farba = Paint::default();
+    farba.set_color_rgba8(r, g, b, 0xff);
+    farba.anti_alias = true;
+    farba
+}
+
+fn linia(x0: f32, y0: f32, x1: f32, y1: f32) -> Option<Path> {
+    let mut pb = PathBuilder::new();
+    pb.move_to(x0, y0);
+    pb.line_to(x1, y1);
+    pb.finish()
+}
+
+fn wypełnij(obraz: &mut Pixmap, ścieżka: Option<Path>, kolor: (u8, u8, u8)) {
+    if let Some(ścieżka) = ścieżka {
+        obraz.fill_path(&ścieżka, &farba(kolor), FillRule::Winding, Transform::identity(), None);
+    }
+}
+
+fn obrysuj(obraz: &mut Pixmap, ścieżka: Option<Path>, kolor: (u8, u8, u8), szerokość: f32) {
+    if let Some(ścieżka) = ścieżka {
+        let obrys = Stroke {
+            width: szerokość,
+            ..Stroke::default()
+        };
+        obraz.stroke_path(&ścieżka, &farba(kolor), &obrys, Transform::identity(), None);
+    }
+}
+
+/// rysuje znak o środku (x, y) i promieniu r.
+fn znak(obraz: &mut Pixmap, znak: Znak, styl: &Styl, x: f32, y: f32, r: f32) {
+    match znak {
+        Znak::Słońce => {
+            wypełnij(obraz, PathBuilder::from_circle(x, y, r * 0.5), styl.znak);
+            for promień in 0..8u8 {
+                let kąt = f32::from(promień) * std::f32::consts::FRAC_PI_4;
+                let (sin, cos) = kąt.sin_cos();
+                obrysuj(
+                    obraz,
+                    linia(x + cos * r * 0.7, y + sin * r * 0.7, x + cos * r, y + sin * r),
+                    styl.znak,
+                    r * 0.12,
+                );
+            }
+        }
+        Znak::Księżyc => {
+            // sierp powstaje z koła przysłoniętego przesuniętym kołem w kolorze ścianki
+            wypełnij(obraz, PathBuilder::from_circle(x, y, r * 0.85), styl.znak);
+            wypełnij(
+                obraz,
+                PathBuilder::from_circle(x + r * 0.4, y - r * 0.2, r * 0.7),
+                styl.ścianka,
+            );
+        }
+        Znak::Skaza => {
+            let d = r * 0.7;
+            obrysuj(obraz, linia(x - d, y - d, x + d, y + d), styl.znak, r * 0.25);
+            obrysuj(obraz, linia(x - d, y + d, x + d, y - d), styl.znak, r * 0.25);
+        }
+    }
+}
+
+fn kość(obraz: &mut Pixmap, rodzaj: RodzajKości, ścianka: &[Znak], x: f32, y: f32) {
+    let styl = styl(rodzaj);
+    let ramka = Rect::from_xywh(x, y, KOŚĆ, KOŚĆ).map(PathBuilder::from_rect);
+    wypełnij(obraz, ramka.clone(), styl.ścianka);
+    obrysuj(obraz, ramka, styl.krawędź, 3.0);
+
+    // znaki rozmieszczone są równo wzdłuż ścianki
+    #[allow(clippy::cast_precision_loss)] // na ściance są najwyżej dwa znaki
+    let miejsca = ścianka.len() as f32 + 1.0;
+    let r = KOŚĆ / miejsca / 2.0 * 0.9;
+    for (i, wyrzucony) in ścianka.iter().enumerate() {
+        #[allow(clippy::cast_precision_loss)]
+        let środek = x + KOŚĆ * (i as f32 + 1.0) / miejsca;
+        znak(obraz, *wyrzucony, &styl, środek, y + KOŚĆ / 2.0, r);
+    }
+}
+
+/// obraz png z kośćmi wyniku, każda grupa kości w osobnym wierszu.
+pub fn png(wynik: &Wynik) -> Result<Vec<u8>, String> {
+    let kolumny = wynik
+        .grupy
+        .iter()
+        .map(|grupa| grupa.ścianki.len())
+        .max()
+        .unwrap_or_default()
+        .max(1);
+    let wiersze = wynik.grupy.len().max(1);
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // stałe wymiary kości
+    let wymiar = |ile: usize| {
+        u32::try_from(ile)
+            .map(|ile| ile * (KOŚĆ + ODSTĘP) as u32 + ODSTĘP as u32)
+            .map_err(|why| why.to_string())
+    };
+    let mut obraz = Pixmap::new(wymiar(kolumny)?, wymiar(wiersze)?)
+        .ok_or_else(|| "niepoprawny rozmiar obrazu".to_owned())?;
+    obraz.fill(Color::from_rgba8(TŁO.0, TŁO.1, TŁO.2, 0xff));
+
+    for (wiersz, grupa) in wynik.grupy.iter().enumerate() {
+        for (kolumna, ścianka) in grupa.ścianki.iter().enumerate() {
+            #[allow(clippy::cast_precision_loss)]
+            let (x, y) = (
+                ODSTĘP + kolumna as f32 * (KOŚĆ + ODSTĘP),
+                ODSTĘP + wiersz as f32 * (KOŚĆ + ODSTĘP),
+            );
+            kość(&mut obraz, grupa.rodzaj, ścianka, x, y);
+        }
+    }
+
+    obraz.encode_png().map_err(|why| why.to_string())
+}
@@ -8,6 +8,11 @@ impl InvalidArgument {
     pub const fn new(string: String) -> Self {
         Self(string)
     }
+
+    /// sam niepoprawny argument, bez opisu błędu.
+    pub fn argument(&self) -> &str {
+        &self.0
+    }
 }
 
 impl std::fmt::Display for InvalidArgument {
@@ -44,8 +49,21 @@ pub enum Arg {
     Long(String, Vec<String>),
 }
 
+// zapis, który parser odczyta z powrotem jako ten sam argument
+impl std::fmt::Display for Arg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        let (początek, params) = match self {
+            Self::Plain(inside) => return write!(f, "{inside}"),
+            Self::Short(ch, params) => (format!("-{ch}"), params),
+            Self::Long(inside, params) => (format!("--{inside}"), params),
+        };
+        write!(f, "{początek}")?;
+        params.iter().try_for_each(|param| write!(f, " {param}"))
+    }
+}
+
 impl Arg {
-    pub fn try_parse(mut args: Args) -> Result<Vec<Self>, impl Error> {
+    pub fn try_parse(mut args: Args) -> Result<Vec<Self>, InvalidArgument> {
         let mut parsed_args = Vec::new();
         // todo
         while let Ok(s) = args.single::<String>() {
@@ -142,4 +160,16 @@ mod test {
             ])
         );
     }
+
+    #[test]
+    fn zapis() {
+        let args = Arg::try_parse(Args::new("zanik 2 -wC --plus 1 3", &[Delimiter::Single(' ')]))
+            .unwrap();
+        let zapis = args.iter().map(Arg::to_string).collect::<Vec<_>>().join(" ");
+        assert_eq!(zapis, "zanik 2 -w c --plus 1 3");
+        assert_eq!(
+            Arg::try_parse(Args::new(&zapis, &[Delimiter::Single(' ')])).ok(),
+            Some(args)
+        );
+    }
 }
@@ -0,0 +1,525 @@
+use crate::zug::{Narzędzie, Wzorzec, Znak};
+use serde::{Deserialize, Serialize};
+use serenity::prelude::TypeMapKey;
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use Znak::{Księżyc as M, Skaza as X, Słońce as S};
+
+/* reguły gry wczytywane z plików toml. domyślnie zgodne ze Zdrżeniem Urojonych Gier */
+
+/// najwięcej kości w jednym rzucie, by wynik zmieścił się w wiadomości.
+pub const NAJWIĘCEJ_KOŚCI: usize = 20;
+pub const ŚCIANKI_WZORCA: usize = 6;
+
+/* # kości */
+
+/// ścianki kości. w pliku reguł każda ścianka zapisana jest znakami `S` (słońce),
+/// `M` (księżyc) oraz `X` (skaza), a pusta ścianka pustym napisem lub `-`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "Vec<String>", into = "Vec<String>")]
+pub struct Kość(Vec<Vec<Znak>>);
+
+impl Kość {
+    fn z_ścianek(ścianki: &[&[Znak]]) -> Self {
+        Self(ścianki.iter().map(|ścianka| ścianka.to_vec()).collect())
+    }
+
+    pub fn ścianki(&self) -> &[Vec<Znak>] {
+        &self.0
+    }
+}
+
+impl TryFrom<Vec<String>> for Kość {
+    type Error = String;
+
+    fn try_from(ścianki: Vec<String>) -> Result<Self, Self::Error> {
+        if ścianki.is_empty() {
+            return Err("kość musi mieć co najmniej jedną ściankę".to_owned());
+        }
+        ścianki
+            .iter()
+            .map(|ścianka| {
+                ścianka
+                    .trim_matches('-')
+                    .chars()
+                    .map(|znak| match znak.to_ascii_uppercase() {
+                        'S' => Ok(S),
+                        'M' => Ok(M),
+                        'X' => Ok(X),
+                        znak => Err(format!(
+                            "nieznany znak ścianki `{znak}`, dozwolone są S, M i X"
+                        )),
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+impl From<Kość> for Vec<String> {
+    fn from(kość: Kość) -> Self {
+        kość.0
+            .iter()
+            .map(|ścianka| {
+                ścianka
+                    .iter()
+                    .map(|znak| match znak {
+                        S => 'S',
+                        M => 'M',
+                        X => 'X',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/* # tabele */
+
+/// wzorzec spoza podstawowych reguł, określony w pliku reguł lub przez gildię.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WłasnyWzorzec {
+    pub kość: Kość,
+    /// inne nazwy, którymi można wybrać wzorzec
+    #[serde(default)]
+    pub aliasy: Vec<String>,
+}
+
+impl WłasnyWzorzec {
+    /// sprawdza, czy wzorzec ma sześć ścianek, a jego nazwy nie zasłaniają wzorców podstawowych.
+    pub fn sprawdź(&self, nazwa: &str) -> Result<(), String> {
+        if self.kość.ścianki().len() != ŚCIANKI_WZORCA {
+            return Err(format!("kość wzorca {nazwa} musi mieć {ŚCIANKI_WZORCA} ścianek"));
+        }
+        for inna in [nazwa].into_iter().chain(self.aliasy.iter().map(String::as_str)) {
+            if inna.is_empty() || inna.contains(char::is_whitespace) {
+                return Err(format!("niepoprawna nazwa wzorca `{inna}`"));
+            }
+            if Wzorzec::podstawowy(inna).is_some() {
+                return Err(format!("nazwa `{inna}` zasłania wzorzec podstawowy"));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Wzorce {
+    pub chojrak: Kość,
+    pub szelma: Kość,
+    /// dodatkowe wzorce według nazwy
+    pub własne: HashMap<String, WłasnyWzorzec>,
+}
+
+impl Default for Wzorce {
+    fn default() -> Self {
+        Self {
+            chojrak: Kość::z_ścianek(&[&[S], &[S], &[S], &[M], &[M], &[]]),
+            szelma: Kość::z_ścianek(&[&[S], &[S], &[M], &[M], &[M], &[]]),
+            własne: HashMap::new(),
+        }
+    }
+}
+
+/// liczba kości wzorca w próbie według wyszkolenia.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Fachy {
+    pub zielony: usize,
+    pub szkolony: usize,
+    pub biegły: usize,
+}
+
+impl Default for Fachy {
+    fn default() -> Self {
+        Self {
+            zielony: 4,
+            szkolony: 5,
+            biegły: 6,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Narzędzia {
+    pub kiepskie: Kość,
+    pub przyzwoite: Kość,
+    pub znakomite: Kość,
+}
+
+impl Default for Narzędzia {
+    fn default() -> Self {
+        Self {
+            kiepskie: Kość::z_ścianek(&[&[X, X], &[X], &[], &[]]),
+            przyzwoite: Kość::z_ścianek(&[&[X], &[X], &[], &[]]),
+            znakomite: Kość::z_ścianek(&[&[X], &[], &[], &[]]),
+        }
+    }
+}
+
+/// jakość narzędzia, którego kością rzuca się za broń danego zasięgu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ZasięgiBroni {
+    pub biała: Narzędzie,
+    pub drzewcowa: Narzędzie,
+    pub miotająca: Narzędzie,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Bronie {
+    pub lekka: ZasięgiBroni,
+    pub średnia: ZasięgiBroni,
+    pub ciężka: ZasięgiBroni,
+}
+
+impl Default for Bronie {
+    fn default() -> Self {
+        Self {
+            lekka: ZasięgiBroni {
+                biała: Narzędzie::Przyzwoite,
+                drzewcowa: Narzędzie::Kiepskie,
+                miotająca: Narzędzie::Kiepskie,
+            },
+            średnia: ZasięgiBroni {
+                biała: Narzędzie::Przyzwoite,
+                drzewcowa: Narzędzie::Przyzwoite,
+                miotająca: Narzędzie::Przyzwoite,
+            },
+            ciężka: ZasięgiBroni {
+                biała: Narzędzie::Znakomite,
+                drzewcowa: Narzędzie::Znakomite,
+                miotająca: Narzędzie::Przyzwoite,
+            },
+        }
+    }
+}
+
+/// zmiana kości broni o danej cesze.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ZasadaCechy {
+    /// o ile stopni lepsze, a gdy ujemne gorsze, jest narzędzie dające kość broni
+    pub jakość: isize,
+    /// dodatkowe kości broni w bitwie
+    pub kości: usize,
+    /// kość zastępująca kość narzędzia
+    pub kość: Option<Kość>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Cechy {
+    pub dwuręczna: ZasadaCechy,
+    pub palna: ZasadaCechy,
+    pub improwizowana: ZasadaCechy,
+    pub mistrzowska: ZasadaCechy,
+}
+
+impl Default for Cechy {
+    fn default() -> Self {
+        Self {
+            dwuręczna: ZasadaCechy {
+                jakość: 1,
+                ..ZasadaCechy::default()
+            },
+            palna: ZasadaCechy {
+                kości: 1,
+                ..ZasadaCechy::default()
+            },
+            improwizowana: ZasadaCechy {
+                jakość: -1,
+                ..ZasadaCechy::default()
+            },
+            mistrzowska: ZasadaCechy {
+                jakość: 1,
+                ..ZasadaCechy::default()
+            },
+        }
+    }
+}
+
+/// liczba skaz, które osłona danej jakości zatrzymuje w bitwie.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JakościOsłony {
+    pub kiepskie: usize,
+    pub przyzwoite: usize,
+    pub znakomite: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Osłony {
+    pub pancerz: JakościOsłony,
+    pub tarcza: JakościOsłony,
+}
+
+impl Default for Osłony {
+    fn default() -> Self {
+        Self {
+            pancerz: JakościOsłony {
+                kiepskie: 1,
+                przyzwoite: 1,
+                znakomite: 2,
+            },
+            tarcza: JakościOsłony {
+                kiepskie: 0,
+                przyzwoite: 1,
+                znakomite: 1,
+            },
+        }
+    }
+}
+
+/// ograniczenia broni używanych jednocześnie w bitwie.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Uzbrojenie {
+    /// ręce gracza. broń dwuręczna zajmuje dwie, pozostałe jedną
+    pub ręce: usize,
+    /// najwięcej broni ciężkich naraz
+    pub ciężkie: usize,
+}
+
+impl Default for Uzbrojenie {
+    fn default() -> Self {
+        Self {
+            ręce: 2,
+            ciężkie: 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Bitwa {
+    /// kości wzorca w bitwie przed modyfikatorami.
+    pub kości: usize,
+}
+
+impl Default for Bitwa {
+    fn default() -> Self {
+        Self { kości: 4 }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Pomoc {
+    /// kości wzorca, które każdy pomocnik dokłada do próby.
+    pub kości: usize,
+}
+
+impl Default for Pomoc {
+    fn default() -> Self {
+        Self { kości: 1 }
+    }
+}
+
+/* # całość */
+
+/// reguły gry. tabele pominięte w pliku przyjmują wartości domyślne.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Reguły {
+    pub wzorce: Wzorce,
+    pub fachy: Fachy,
+    pub narzędzia: Narzędzia,
+    pub bronie: Bronie,
+    pub cechy: Cechy,
+    pub osłony: Osłony,
+    pub uzbrojenie: Uzbrojenie,
+    pub bitwa: Bitwa,
+    pub pomoc: Pomoc,
+}
+
+impl Reguły {
+    pub fn z_tekstu(tekst: &str) -> Result<Self, Box<dyn Error>> {
+        let reguły: Self = toml::from_str(tekst)?;
+        reguły.sprawdź()?;
+        Ok(reguły)
+    }
+
+    fn z_pliku(ścieżka: &Path) -> Result<Self, Box<dyn Error>> {
+        let tekst = fs::read_to_string(ścieżka).map_err(|why| {
+            format!("nie udało się odczytać reguł {}: {why}", ścieżka.display())
+        })?;
+        Self::z_tekstu(&tekst)
+            .map_err(|why| format!("niepoprawne reguły {}: {why}", ścieżka.display()).into())
+    }
+
+    fn sprawdź(&self) -> Result<(), Box<dyn Error>> {
+        let Fachy {
+            zielony,
+            szkolony,
+            biegły,
+        } = self.fachy;
+        if let Some(kości) = [zielony, szkolony, biegły, self.bitwa.kości]
+            .into_iter()
+            .find(|kości| !(1..=NAJWIĘCEJ_KOŚCI).contains(kości))
+        {
+            return Err(format!("liczba kości {kości} spoza zakresu 1-{NAJWIĘCEJ_KOŚCI}").into());
+        }
+        let Cechy {
+            dwuręczna,
+            palna,
+            improwizowana,
+            mistrzowska,
+        } = &self.cechy;
+        if let Some(kości) = [dwuręczna, palna, improwizowana, mistrzowska]
+            .into_iter()
+            .map(|zasada| zasada.kości)
+            .find(|kości| *kości >= NAJWIĘCEJ_KOŚCI)
+        {
+            return Err(format!("zbyt wiele dodatkowych kości broni: {kości}").into());
+        }
+        let Osłony { pancerz, tarcza } = self.osłony;
+        if let Some(skazy) = [pancerz, tarcza]
+            .into_iter()
+            .flat_map(|osłona| [osłona.kiepskie, osłona.przyzwoite, osłona.znakomite])
+            .find(|skazy| *skazy > NAJWIĘCEJ_KOŚCI)
+        {
+            return Err(format!("osłona zatrzymuje zbyt wiele skaz: {skazy}").into());
+        }
+        if self.uzbrojenie.ręce == 0 {
+            return Err("gracz musi mieć co najmniej jedną rękę".into());
+        }
+        if self.pomoc.kości > NAJWIĘCEJ_KOŚCI {
+            return Err(format!("zbyt wiele kości pomocy: {}", self.pomoc.kości).into());
+        }
+        for (nazwa, wzorzec) in &self.wzorce.własne {
+            wzorzec.sprawdź(nazwa)?;
+        }
+        Ok(())
+    }
+}
+
+/* # zestawy */
+
+/// pliki reguł podane w konfiguracji bota.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UstawieniaReguł {
+    /// reguły obowiązujące w gildiach, które nie wybrały zestawu.
+    pub plik: Option<PathBuf>,
+    /// zestawy reguł, spośród których gildia może wybrać własny, według nazwy.
+    pub zestawy: HashMap<String, PathBuf>,
+}
+
+#[derive(Default)]
+pub struct Zestawy {
+    pub domyślne: Arc<Reguły>,
+    pub nazwane: HashMap<String, Arc<Reguły>>,
+}
+
+impl Zestawy {
+    /// wczytuje i sprawdza wszystkie pliki reguł, by błędy wyszły na jaw przy uruchomieniu.
+    pub fn wczytaj(ustawienia: &UstawieniaReguł) -> Result<Self, Box<dyn Error>> {
+        let domyślne = match &ustawienia.plik {
+            Some(plik) => Reguły::z_pliku(plik)?,
+            None => Reguły::default(),
+        };
+        let nazwane = ustawienia
+            .zestawy
+            .iter()
+            .map(|(nazwa, plik)| Ok((nazwa.to_lowercase(), Arc::new(Reguły::z_pliku(plik)?))))
+            .collect::<Result<_, Box<dyn Error>>>()?;
+        Ok(Self {
+            domyślne: Arc::new(domyślne),
+            nazwane,
+        })
+    }
+
+    /// reguły wybranego zestawu, a bez wyboru lub dla zestawu usuniętego z konfiguracji domyślne.
+    pub fn wybierz(&self, nazwa: Option<&str>) -> Arc<Reguły> {
+        nazwa
+            .and_then(|nazwa| {
+                let reguły = self.nazwane.get(nazwa);
+                if reguły.is_none() {
+                    tracing::warn!(zestaw = nazwa, "brak zestawu reguł, obowiązują domyślne");
+                }
+                reguły
+            })
+            .unwrap_or(&self.domyślne)
+            .clone()
+    }
+}
+
+pub struct RegułyHolder;
+
+impl TypeMapKey for RegułyHolder {
+    type Value = Zestawy;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reguły() {
+        assert_eq!(
+            Reguły::z_tekstu(include_str!("../reguły.example.toml")).unwrap(),
+            Reguły::default()
+        );
+
+        let domowe = Reguły::z_tekstu(
+            r#"
+            [wzorce]
+            szelma = ["S", "SM", "M", "M", "", ""]
+
+            [fachy]
+            "biegły" = 7
+            "#,
+        )
+        .unwrap();
+        assert_eq!(domowe.wzorce.szelma.ścianki()[1], vec![S, M]);
+        assert_eq!(domowe.wzorce.chojrak, Wzorce::default().chojrak);
+        assert_eq!(domowe.fachy.biegły, 7);
+        assert_eq!(domowe.fachy.zielony, 4);
+
+        let własne = Reguły::z_tekstu(
+            r#"
+            [wzorce."własne".olbrzym]
+            "kość" = ["SS", "S", "M", "M", "-", ""]
+            aliasy = ["olbrzymka"]
+            "#,
+        )
+        .unwrap();
+        let olbrzym = &własne.wzorce.własne["olbrzym"];
+        assert_eq!(olbrzym.kość.ścianki()[0], vec![S, S]);
+        assert!(olbrzym.kość.ścianki()[4].is_empty());
+        assert_eq!(Vec::from(olbrzym.kość.clone())[4], "");
+        assert!(Reguły::z_tekstu("[wzorce.\"własne\".olbrzym]\n\"kość\" = [\"S\"]").is_err());
+        assert!(Reguły::z_tekstu(
+            "[wzorce.\"własne\".chojraczek]\n\"kość\" = [\"S\", \"S\", \"S\", \"M\", \"M\", \"\"]"
+        )
+        .is_err());
+
+        assert!(Reguły::z_tekstu("[wzorce]\nchojrak = [\"S\", \"Q\"]").is_err());
+        assert!(Reguły::z_tekstu("[wzorce]\nchojrak = []").is_err());
+        assert!(Reguły::z_tekstu("[fachy]\nzielony = 0").is_err());
+        assert!(Reguły::z_tekstu(
+            "[bronie.lekka]\n\"biała\" = \"tępe\"\ndrzewcowa = \"kiepskie\"\n\"miotająca\" = \"kiepskie\""
+        )
+        .is_err());
+        assert!(Reguły::z_tekstu(
+            "[\"osłony\".tarcza]\nkiepskie = 0\nprzyzwoite = 1\nznakomite = 21"
+        )
+        .is_err());
+        assert!(Reguły::z_tekstu("[uzbrojenie]\n\"ręce\" = 0").is_err());
+        assert!(Reguły::z_tekstu("[pomoc]\n\"kości\" = 21").is_err());
+        assert!(Reguły::z_tekstu("[magia]").is_err());
+    }
+}
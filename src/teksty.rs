@@ -0,0 +1,249 @@
+use crate::ustawienia::Język;
+use rand::seq::SliceRandom;
+use std::{collections::HashMap, sync::OnceLock};
+
+/* katalogi tekstów wyświetlanych graczom. brakujące tłumaczenia zastępowane są polskimi */
+
+type Katalog = HashMap<String, String>;
+
+const POLSKI: &str = include_str!("teksty/pl.toml");
+const ANGIELSKI: &str = include_str!("teksty/en.toml");
+
+/// zagnieżdżone tabele zamieniane są na klucze rozdzielone kropkami, np. `błąd.brak_wzorca`.
+fn spłaszcz(prefiks: &str, wartość: toml::Value, katalog: &mut Katalog) {
+    match wartość {
+        toml::Value::Table(tabela) => {
+            for (klucz, wartość) in tabela {
+                let klucz = if prefiks.is_empty() {
+                    klucz
+                } else {
+                    format!("{prefiks}.{klucz}")
+                };
+                spłaszcz(&klucz, wartość, katalog);
+            }
+        }
+        toml::Value::String(tekst) => {
+            katalog.insert(prefiks.to_owned(), tekst);
+        }
+        wartość => {
+            katalog.insert(prefiks.to_owned(), wartość.to_string());
+        }
+    }
+}
+
+fn wczytaj(źródło: &str) -> Result<Katalog, toml::de::Error> {
+    let mut katalog = Katalog::new();
+    spłaszcz("", źródło.parse()?, &mut katalog);
+    Ok(katalog)
+}
+
+fn katalog(język: Język) -> &'static Katalog {
+    static KATALOGI: [OnceLock<Katalog>; 2] = [OnceLock::new(), OnceLock::new()];
+    let (indeks, źródło) = match język {
+        Język::Polski => (0, POLSKI),
+        Język::Angielski => (1, ANGIELSKI),
+    };
+    KATALOGI[indeks].get_or_init(|| {
+        wczytaj(źródło).unwrap_or_else(|why| {
+            tracing::error!(język = język.kod(), błąd = %why, "niepoprawny katalog tekstów");
+            Katalog::new()
+        })
+    })
+}
+
+/// tekst w wybranym języku lub po polsku, jeśli nie został przetłumaczony.
+pub fn znajdź(język: Język, klucz: &str) -> Option<&'static str> {
+    katalog(język)
+        .get(klucz)
+        .or_else(|| katalog(Język::Polski).get(klucz))
+        .map(String::as_str)
+}
+
+/// zastępuje `{nazwa}` wartością, o ile ją podano. pozostałe nawiasy zostają nietknięte.
+pub fn wypełnij<F>(szablon: &str, wartość: F) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut wynik = String::with_capacity(szablon.len());
+    let mut reszta = szablon;
+    while let Some(początek) = reszta.find('{') {
+        wynik.push_str(&reszta[..początek]);
+        let po = &reszta[początek + 1..];
+        if let Some((koniec, tekst)) = po
+            .find('}')
+            .and_then(|koniec| Some((koniec, wartość(&po[..koniec])?)))
+        {
+            wynik.push_str(&tekst);
+            reszta = &po[koniec + 1..];
+        } else {
+            wynik.push('{');
+            reszta = po;
+        }
+    }
+    wynik.push_str(reszta);
+    wynik
+}
+
+/// tekst z podstawionymi wartościami. `{krzycz}` zawsze wskazuje, jak uzyskać wsparcie.
+pub fn wstaw(język: Język, klucz: &str, wartości: &[(&str, &str)]) -> String {
+    Mowa::from(język).wstaw(klucz, wartości)
+}
+
+pub fn tekst(język: Język, klucz: &str) -> String {
+    wstaw(język, klucz, &[])
+}
+
+/* # mowa */
+
+/// teksty odpowiedzi dla nadawcy: język, szablony gildii zastępujące teksty katalogu oraz
+/// wartości dostępne w każdym szablonie.
+#[derive(Clone, Default)]
+pub struct Mowa {
+    pub język: Język,
+    pub szablony: HashMap<String, String>,
+    pub opisy: HashMap<String, Vec<String>>,
+    /// wzmianka o nadawcy, `{gracz}` w szablonach
+    pub gracz: Option<String>,
+    /// nazwa nadawcy na serwerze, `{postać}` w szablonach
+    pub postać: Option<String>,
+}
+
+impl From<Język> for Mowa {
+    fn from(język: Język) -> Self {
+        Self {
+            język,
+            ..Self::default()
+        }
+    }
+}
+
+impl Mowa {
+    /// czy któryś z szablonów gildii korzysta z wartości, np. `postać`.
+    pub fn korzysta(&self, nazwa: &str) -> bool {
+        let nawias = format!("{{{nazwa}}}");
+        self.szablony
+            .values()
+            .chain(self.opisy.values().flatten())
+            .any(|szablon| szablon.contains(&nawias))
+    }
+
+    fn wypełnij(&self, szablon: &str, wartości: &[(&str, &str)]) -> String {
+        wypełnij(szablon, |nazwa| {
+            wartości
+                .iter()
+                .find(|(klucz, _)| *klucz == nazwa)
+                .map(|(_, wartość)| (*wartość).to_owned())
+                .or_else(|| match nazwa {
+                    "krzycz" => Some(self.tekst("krzycz")),
+                    "gracz" => self.gracz.clone(),
+                    "postać" => self.postać.clone(),
+                    _ => None,
+                })
+        })
+    }
+
+    pub fn wstaw(&self, klucz: &str, wartości: &[(&str, &str)]) -> String {
+        let szablon = self
+            .szablony
+            .get(klucz)
+            .map(String::as_str)
+            .or_else(|| znajdź(self.język, klucz))
+            .unwrap_or(klucz);
+        self.wypełnij(szablon, wartości)
+    }
+
+    pub fn tekst(&self, klucz: &str) -> String {
+        self.wstaw(klucz, &[])
+    }
+
+    pub fn liczebnik(&self, klucz: &str, liczba: usize) -> String {
+        liczebnik(self.język, klucz, liczba)
+    }
+
+    /// losowy opis z tabeli gildii dla wydźwięku wyniku, o ile jakiś ustawiono.
+    pub fn opis(&self, wydźwięk: &str, wartości: &[(&str, &str)]) -> Option<String> {
+        let opis = self.opisy.get(wydźwięk)?.choose(&mut rand::thread_rng())?;
+        Some(self.wypełnij(opis, wartości))
+    }
+}
+
+/// tekst o wskazanym kluczu, a bez tłumaczenia podany tekst polski.
+pub fn przetłumacz(język: Język, klucz: &str, polski: &'static str) -> &'static str {
+    katalog(język).get(klucz).map_or(polski, String::as_str)
+}
+
+/// liczebnik z rzeczownikiem w odpowiedniej formie, np. `3 słońca`. formy w katalogu
+/// rozdzielone są znakiem `|`.
+pub fn liczebnik(język: Język, klucz: &str, liczba: usize) -> String {
+    let formy = znajdź(język, klucz).unwrap_or(klucz).split('|').collect::<Vec<_>>();
+    let rzeczownik = formy
+        .get(język.forma(liczba))
+        .or_else(|| formy.last())
+        .unwrap_or(&klucz);
+    format!("{liczba} {rzeczownik}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::komendy::{Komenda, KOMENDY};
+    use std::collections::BTreeSet;
+
+    fn nawiasy(tekst: &str) -> BTreeSet<String> {
+        tekst
+            .split('{')
+            .skip(1)
+            .filter_map(|część| część.split_once('}'))
+            .map(|(nazwa, _)| nazwa.to_owned())
+            .collect()
+    }
+
+    /// klucze tłumaczeń pomocy komendy wraz z jej podkomendami.
+    fn klucze_pomocy(komenda: &Komenda, prefiks: &str, klucze: &mut Vec<String>) {
+        let ścieżka = format!("{prefiks}{}", komenda.nazwa);
+        klucze.push(format!("komendy.{ścieżka}"));
+        klucze.extend(komenda.opcje.iter().map(|opcja| format!("opcje.{}", opcja.wartość())));
+        klucze.extend((0..komenda.przykłady.len()).map(|i| format!("przykłady.{ścieżka}.{i}")));
+        for podkomenda in komenda.podkomendy {
+            klucze_pomocy(podkomenda, &format!("{ścieżka}."), klucze);
+        }
+    }
+
+    #[test]
+    fn katalogi() {
+        let polski = wczytaj(POLSKI).unwrap();
+        let angielski = wczytaj(ANGIELSKI).unwrap();
+        for (klucz, tekst) in &polski {
+            let tłumaczenie = angielski.get(klucz);
+            assert!(tłumaczenie.is_some(), "brak tłumaczenia {klucz}");
+            assert_eq!(tłumaczenie.map(|t| nawiasy(t)), Some(nawiasy(tekst)), "{klucz}");
+        }
+
+        let mut pomoc = Vec::new();
+        for komenda in KOMENDY {
+            klucze_pomocy(komenda, "", &mut pomoc);
+        }
+        for klucz in pomoc {
+            assert!(angielski.contains_key(&klucz), "brak tłumaczenia pomocy {klucz}");
+        }
+
+        assert_eq!(
+            wypełnij("{a} i {b} oraz {c", |nazwa| (nazwa == "a").then(|| "1".to_owned())),
+            "1 i {b} oraz {c"
+        );
+        assert_eq!(liczebnik(Język::Polski, "wynik.księżyc", 12), "12 księżyców");
+        assert_eq!(liczebnik(Język::Polski, "wynik.słońce", 23), "23 słońca");
+        assert_eq!(liczebnik(Język::Angielski, "wynik.skaza", 1), "1 flaw");
+
+        let mut mowa = Mowa::from(Język::Angielski);
+        mowa.gracz = Some("<@1>".to_owned());
+        mowa.szablony
+            .insert("zanik.porażka".to_owned(), "{gracz}: {jakość} pęka!".to_owned());
+        assert_eq!(
+            mowa.wstaw("zanik.porażka", &[("jakość", "kilof")]),
+            "<@1>: kilof pęka!"
+        );
+        assert_eq!(mowa.tekst("zanik.sukces"), "success! your gear keeps its durability.");
+        assert!(mowa.korzysta("gracz") && !mowa.korzysta("postać"));
+    }
+}
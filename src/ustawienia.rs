@@ -0,0 +1,371 @@
+use crate::{
+    magazyn::Tabela,
+    reguły::{Reguły, RegułyHolder, WłasnyWzorzec},
+    teksty::{self, Mowa},
+    Nadawca,
+};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use serenity::{
+    client::Context,
+    model::{
+        misc::Mentionable,
+        prelude::{ChannelId, GuildId, RoleId, UserId},
+    },
+    prelude::TypeMapKey,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::Arc,
+};
+
+/* ustawienia gildii zmieniane przez administratorów komendą `ustawienia` */
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Język {
+    #[default]
+    #[serde(rename = "pl")]
+    Polski,
+    #[serde(rename = "en")]
+    Angielski,
+}
+
+impl Język {
+    pub fn try_parse(s: &str) -> Option<Self> {
+        match s {
+            "pl" | "polski" => Some(Self::Polski),
+            "en" | "angielski" => Some(Self::Angielski),
+            _ => None,
+        }
+    }
+
+    pub const fn kod(self) -> &'static str {
+        match self {
+            Self::Polski => "pl",
+            Self::Angielski => "en",
+        }
+    }
+
+    /// nazwa języka w języku odpowiedzi.
+    pub fn nazwa(self, język: Self) -> String {
+        teksty::tekst(język, &format!("języki.{}", self.kod()))
+    }
+
+    /// indeks formy rzeczownika po liczebniku. polski rozróżnia jedną, kilka (2-4) oraz
+    /// wiele rzeczy, angielski jedną oraz wiele.
+    pub const fn forma(self, liczba: usize) -> usize {
+        match (self, liczba, liczba % 10, liczba % 100) {
+            (_, 1, ..) => 0,
+            (Self::Polski, _, 2..=4, dziesiątki) if dziesiątki < 12 || dziesiątki > 14 => 1,
+            (Self::Polski, ..) => 2,
+            (Self::Angielski, ..) => 1,
+        }
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub enum Glify {
+    #[default]
+    Unicode,
+    Ascii,
+    Własne { słońce: String, księżyc: String },
+}
+
+impl fmt::Display for Glify {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unicode => write!(f, "unicode"),
+            Self::Ascii => write!(f, "ascii"),
+            Self::Własne { słońce, księżyc } => write!(f, "własne ({słońce} {księżyc})"),
+        }
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UstawieniaGildii {
+    pub prefiks: Option<String>,
+    pub język: Język,
+    pub glify: Glify,
+    pub rola_mg: Option<RoleId>,
+    pub kanały: HashSet<ChannelId>,
+    pub ukryte_rzuty: bool,
+    pub obrazy: bool,
+    /// teksty odpowiedzi zastępujące katalog, według klucza katalogu
+    pub szablony: HashMap<String, String>,
+    /// losowe opisy dołączane do wyników, według wydźwięku
+    pub opisy: HashMap<String, Vec<String>>,
+    /// nazwa zestawu reguł z konfiguracji bota, bez niej obowiązują reguły domyślne
+    pub reguły: Option<String>,
+    /// wzorce gildii, uzupełniające wzorce z reguł
+    pub wzorce: HashMap<String, WłasnyWzorzec>,
+}
+
+impl UstawieniaGildii {
+    pub fn rzuty_dozwolone(&self, kanał: ChannelId) -> bool {
+        self.kanały.is_empty() || self.kanały.contains(&kanał)
+    }
+}
+
+#[allow(clippy::match_bool)] // i think this is more readable
+const fn przełącznik(
+    wartość: bool,
+    włączony: &'static str,
+    wyłączony: &'static str,
+) -> &'static str {
+    match wartość {
+        true => włączony,
+        false => wyłączony,
+    }
+}
+
+fn linia(język: Język, klucz: &str, wartość: &str) -> String {
+    teksty::wstaw(język, &format!("ustawienia.{klucz}"), &[("wartość", wartość)])
+}
+
+impl UstawieniaGildii {
+    /// teksty odpowiedzi w języku gildii, z jej szablonami.
+    pub fn mowa(self) -> Mowa {
+        Mowa {
+            język: self.język,
+            szablony: self.szablony,
+            opisy: self.opisy,
+            ..Mowa::default()
+        }
+    }
+
+    /// ustawienia w postaci wyświetlanej administratorom.
+    pub fn opis(&self, język: Język) -> String {
+        let tekst = |klucz: &str| teksty::tekst(język, &format!("ustawienia.{klucz}"));
+        let glify = match &self.glify {
+            Glify::Własne { słońce, księżyc } => {
+                format!("{} ({słońce} {księżyc})", tekst("własne"))
+            }
+            glify => glify.to_string(),
+        };
+        let szablony = if self.szablony.is_empty() {
+            tekst("brak")
+        } else {
+            self.szablony.keys().sorted().join(", ")
+        };
+        let opisy = if self.opisy.is_empty() {
+            tekst("brak")
+        } else {
+            self.opisy
+                .iter()
+                .sorted()
+                .map(|(wydźwięk, opisy)| format!("{wydźwięk} ({})", opisy.len()))
+                .join(", ")
+        };
+        let wzorce = if self.wzorce.is_empty() {
+            tekst("brak")
+        } else {
+            self.wzorce
+                .iter()
+                .sorted_by_key(|(nazwa, _)| *nazwa)
+                .map(|(nazwa, wzorzec)| match wzorzec.aliasy.as_slice() {
+                    [] => nazwa.clone(),
+                    aliasy => format!("{nazwa} ({})", aliasy.join(", ")),
+                })
+                .join(", ")
+        };
+        let kanały = if self.kanały.is_empty() {
+            tekst("wszystkie")
+        } else {
+            self.kanały
+                .iter()
+                .map(|kanał| kanał.mention().to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        [
+            linia(
+                język,
+                "prefiks",
+                &self.prefiks.clone().unwrap_or_else(|| tekst("domyślny")),
+            ),
+            linia(język, "język", &self.język.nazwa(język)),
+            linia(język, "glify", &glify),
+            linia(
+                język,
+                "mg",
+                &self
+                    .rola_mg
+                    .map_or_else(|| tekst("brak"), |rola| rola.mention().to_string()),
+            ),
+            linia(język, "kanały", &kanały),
+            linia(
+                język,
+                "ukryte",
+                &tekst(przełącznik(self.ukryte_rzuty, "dozwolone", "zabronione")),
+            ),
+            linia(
+                język,
+                "obrazy",
+                &tekst(przełącznik(self.obrazy, "włączone", "wyłączone")),
+            ),
+            linia(
+                język,
+                "reguły",
+                &self.reguły.clone().unwrap_or_else(|| tekst("podstawowe")),
+            ),
+            linia(język, "wzorce", &wzorce),
+            linia(język, "szablony", &szablony),
+            linia(język, "opisy", &opisy),
+        ]
+        .join("\n")
+    }
+}
+
+pub struct UstawieniaHolder;
+
+impl TypeMapKey for UstawieniaHolder {
+    type Value = HashMap<GuildId, UstawieniaGildii>;
+}
+
+impl Tabela for UstawieniaHolder {
+    const NAZWA: &'static str = "ustawienia";
+}
+
+/* ustawienia gracza zmieniane przez niego samego komendą `preferencje` */
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UstawieniaGracza {
+    pub opisowe: bool,
+    /// język odpowiedzi gracza, bez niego obowiązuje język gildii.
+    pub język: Option<Język>,
+}
+
+impl UstawieniaGracza {
+    pub fn opis(&self, język: Język) -> String {
+        let tekst = |klucz: &str| teksty::tekst(język, &format!("ustawienia.{klucz}"));
+        [
+            linia(
+                język,
+                "opisowe",
+                &tekst(przełącznik(self.opisowe, "włączone", "wyłączone")),
+            ),
+            linia(
+                język,
+                "język",
+                &self
+                    .język
+                    .map_or_else(|| tekst("serwer"), |wybrany| wybrany.nazwa(język)),
+            ),
+        ]
+        .join("\n")
+    }
+}
+
+pub struct UstawieniaGraczaHolder;
+
+impl TypeMapKey for UstawieniaGraczaHolder {
+    type Value = HashMap<UserId, UstawieniaGracza>;
+}
+
+impl Tabela for UstawieniaGraczaHolder {
+    const NAZWA: &'static str = "gracze";
+}
+
+pub async fn gracza(ctx: &Context, nadawca: &Nadawca) -> UstawieniaGracza {
+    let data = ctx.data.read().await;
+    data.get::<UstawieniaGraczaHolder>()
+        .and_then(|holder| holder.get(&nadawca.gracz).cloned())
+        .unwrap_or_default()
+}
+
+/// ustawienia gildii, w której wysłano komendę, lub domyślne poza gildią.
+pub async fn gildii(ctx: &Context, nadawca: &Nadawca) -> UstawieniaGildii {
+    let data = ctx.data.read().await;
+    nadawca
+        .gildia
+        .and_then(|gildia| data.get::<UstawieniaHolder>()?.get(&gildia).cloned())
+        .unwrap_or_default()
+}
+
+/// reguły gry wybrane przez gildię nadawcy, uzupełnione o jej wzorce.
+pub async fn reguły(ctx: &Context, nadawca: &Nadawca) -> Arc<Reguły> {
+    let gildia = gildii(ctx, nadawca).await;
+    let reguły = ctx
+        .data
+        .read()
+        .await
+        .get::<RegułyHolder>()
+        .map_or_else(Arc::default, |zestawy| zestawy.wybierz(gildia.reguły.as_deref()));
+    if gildia.wzorce.is_empty() {
+        reguły
+    } else {
+        let mut reguły = Reguły::clone(&reguły);
+        reguły.wzorce.własne.extend(gildia.wzorce);
+        Arc::new(reguły)
+    }
+}
+
+/// nazwa nadawcy na serwerze, a poza nim nazwa użytkownika.
+pub async fn postać(ctx: &Context, nadawca: &Nadawca) -> Option<String> {
+    match nadawca.gildia {
+        Some(gildia) => gildia
+            .member(ctx, nadawca.gracz)
+            .await
+            .ok()
+            .map(|członek| członek.display_name().into_owned()),
+        None => nadawca.gracz.to_user(ctx).await.ok().map(|gracz| gracz.name),
+    }
+}
+
+/// teksty odpowiedzi dla nadawcy, w języku wybranym przez gracza lub obowiązującym
+/// w gildii, z szablonami gildii.
+pub async fn mowa(ctx: &Context, nadawca: &Nadawca) -> Mowa {
+    let gildia = gildii(ctx, nadawca).await;
+    let język = gracza(ctx, nadawca).await.język.unwrap_or(gildia.język);
+    let mut mowa = Mowa {
+        język,
+        gracz: Some(nadawca.gracz.mention().to_string()),
+        ..gildia.mowa()
+    };
+    // nazwę pobiera się tylko wtedy, gdy któryś z szablonów gildii z niej korzysta
+    if mowa.korzysta("postać") {
+        mowa.postać = postać(ctx, nadawca).await;
+    }
+    mowa
+}
+
+/// czy autor komendy posiada rolę mistrza gry ustawioną w gildii.
+pub async fn czy_mg(ctx: &Context, nadawca: &Nadawca) -> bool {
+    if let Some(rola) = gildii(ctx, nadawca).await.rola_mg
+        && let Some(gildia) = nadawca.gildia
+    {
+        gildia
+            .member(ctx, nadawca.gracz)
+            .await
+            .map_or(false, |członek| członek.roles.contains(&rola))
+    } else {
+        false
+    }
+}
+
+/// czy autor komendy może zarządzać sprzętem gildii. dopóki gildia nie ustawi roli mistrza
+/// gry, zarządzają nim administratorzy serwera.
+pub async fn czy_zarządca(ctx: &Context, nadawca: &Nadawca) -> bool {
+    if gildii(ctx, nadawca).await.rola_mg.is_some() {
+        return czy_mg(ctx, nadawca).await;
+    }
+    let Some(gildia) = nadawca.gildia else {
+        return false;
+    };
+    match gildia.member(ctx, nadawca.gracz).await {
+        // właściciel serwera ma wszystkie uprawnienia
+        Ok(członek) => członek
+            .permissions(ctx)
+            .await
+            .map_or(false, |uprawnienia| uprawnienia.administrator()),
+        Err(_) => false,
+    }
+}
+
+/// czy autor komendy może rzucać na kanale, mistrz gry może rzucać na każdym.
+pub async fn rzuty_dozwolone(ctx: &Context, nadawca: &Nadawca) -> bool {
+    gildii(ctx, nadawca).await.rzuty_dozwolone(nadawca.kanał) || czy_mg(ctx, nadawca).await
+}
@@ -0,0 +1,413 @@
+use crate::{
+    obrazy,
+    teksty::Mowa,
+    ustawienia::{self, Glify},
+    zug::{Grupa, RodzajKości, Wynik, Znak},
+    Nadawca,
+};
+use itertools::Itertools;
+use serenity::{
+    builder::{CreateComponents, CreateEmbed, CreateInteractionResponseData, CreateMessage},
+    client::Context,
+    http::AttachmentType,
+};
+use std::{borrow::Cow, cmp::Ordering};
+
+/* wyświetlanie wyników rzutów zgodnie z ustawieniami gildii */
+
+const SŁOŃCE: &str = "\u{1d6af}";
+const KSIĘŻYC: &str = "\u{1d6b2}";
+const SKAZA: &str = "X";
+
+/// treść odpowiedzi wraz z opcjonalnym osadzeniem, obrazem png oraz przyciskami.
+pub struct Odpowiedź {
+    pub treść: String,
+    pub osadzenie: Option<CreateEmbed>,
+    pub obraz: Option<Vec<u8>>,
+    /// składniki odpowiedzi innej niż rzut, np. potwierdzenie oddania broni
+    pub przyciski: Option<CreateComponents>,
+}
+
+impl From<String> for Odpowiedź {
+    fn from(treść: String) -> Self {
+        Self {
+            treść,
+            osadzenie: None,
+            obraz: None,
+            przyciski: None,
+        }
+    }
+}
+
+pub fn załącznik(obraz: &[u8]) -> AttachmentType<'static> {
+    AttachmentType::Bytes {
+        data: Cow::Owned(obraz.to_vec()),
+        filename: obrazy::NAZWA_PLIKU.to_owned(),
+    }
+}
+
+impl Odpowiedź {
+    /// wiadomość z obrazem osadzonym w osadzeniu, o ile go wygenerowano.
+    pub fn wiadomość<'a, 'b>(&self, m: &'b mut CreateMessage<'a>) -> &'b mut CreateMessage<'a> {
+        m.content(&self.treść);
+        if let Some(obraz) = &self.obraz {
+            m.add_file(załącznik(obraz));
+        }
+        if let Some(osadzenie) = &self.osadzenie {
+            let mut osadzenie = osadzenie.clone();
+            if self.obraz.is_some() {
+                osadzenie.attachment(obrazy::NAZWA_PLIKU);
+            }
+            m.set_embed(osadzenie);
+        }
+        if let Some(przyciski) = &self.przyciski {
+            m.components(|c| {
+                *c = przyciski.clone();
+                c
+            });
+        }
+        m
+    }
+
+    /// odpowiedź na interakcję nie przyjmuje plików, obraz wysyłany jest osobno.
+    pub fn dane<'a>(
+        &self,
+        d: &'a mut CreateInteractionResponseData,
+    ) -> &'a mut CreateInteractionResponseData {
+        d.content(&self.treść);
+        if let Some(osadzenie) = &self.osadzenie {
+            d.add_embed(osadzenie.clone());
+        }
+        if let Some(przyciski) = &self.przyciski {
+            d.components(|c| {
+                *c = przyciski.clone();
+                c
+            });
+        }
+        d
+    }
+}
+
+fn glif(glify: &Glify, znak: Znak) -> &str {
+    match (glify, znak) {
+        (Glify::Unicode, Znak::Słońce) => SŁOŃCE,
+        (Glify::Unicode, Znak::Księżyc) => KSIĘŻYC,
+        (Glify::Ascii, Znak::Słońce) => "S",
+        (Glify::Ascii, Znak::Księżyc) => "M",
+        (Glify::Własne { słońce, .. }, Znak::Słońce) => słońce,
+        (Glify::Własne { księżyc, .. }, Znak::Księżyc) => księżyc,
+        (_, Znak::Skaza) => SKAZA,
+    }
+}
+
+/// kości wzorców w nawiasach kwadratowych, kości narzędzi i broni w okrągłych.
+fn kość(glify: &Glify, rodzaj: RodzajKości, ścianka: &[Znak]) -> String {
+    let znaki = ścianka.iter().map(|znak| glif(glify, *znak)).collect::<String>();
+    match rodzaj {
+        RodzajKości::Wzorzec | RodzajKości::Pomoc => format!("[{znaki}]"),
+        RodzajKości::Narzędzie | RodzajKości::Broń => format!("({znaki})"),
+    }
+}
+
+fn suma(glify: &Glify, mowa: &Mowa, wynik: &Wynik) -> String {
+    let suma = [Znak::Słońce, Znak::Księżyc]
+        .into_iter()
+        .map(|znak| glif(glify, znak).repeat(wynik.liczba(znak)))
+        .collect::<String>();
+    if suma.is_empty() {
+        mowa.tekst("wynik.pusto")
+    } else {
+        suma
+    }
+}
+
+fn tytuł(mowa: &Mowa, wynik: &Wynik) -> String {
+    mowa.tekst(&format!("rzut.{}", wynik.rzut))
+}
+
+/// nazwa grupy kości. narzędzia nazwane są jakością, pomoc pomocnikiem, a wzorce i bronie
+/// własnymi nazwami.
+fn nazwa_grupy(mowa: &Mowa, grupa: &Grupa) -> String {
+    match grupa.rodzaj {
+        RodzajKości::Narzędzie => {
+            let jakość = mowa.tekst(&format!("jakość.{}", grupa.nazwa));
+            mowa.wstaw("wynik.narzędzie", &[("jakość", &jakość)])
+        }
+        RodzajKości::Pomoc => mowa.wstaw("wynik.pomoc", &[("pomocnik", &grupa.nazwa)]),
+        RodzajKości::Wzorzec | RodzajKości::Broń => grupa.nazwa.clone(),
+    }
+}
+
+pub fn osadzenie(glify: &Glify, mowa: &Mowa, wynik: &Wynik) -> CreateEmbed {
+    let mut osadzenie = CreateEmbed::default();
+    osadzenie
+        .title(tytuł(mowa, wynik))
+        .description(suma(glify, mowa, wynik));
+    for grupa in &wynik.grupy {
+        let kości = grupa
+            .ścianki
+            .iter()
+            .map(|ścianka| kość(glify, grupa.rodzaj, ścianka))
+            .collect::<Vec<_>>();
+        osadzenie.field(
+            nazwa_grupy(mowa, grupa),
+            if kości.is_empty() {
+                mowa.tekst("wynik.brak_kości")
+            } else {
+                kości.join(" ")
+            },
+            true,
+        );
+    }
+    if wynik.osłonięte > 0 {
+        osadzenie.field(
+            mowa.tekst("wynik.osłony"),
+            format!("{} × {}", glif(glify, Znak::Skaza), wynik.osłonięte),
+            true,
+        );
+    }
+    osadzenie.field(
+        mowa.tekst("wynik.razem"),
+        [Znak::Słońce, Znak::Księżyc, Znak::Skaza]
+            .into_iter()
+            .map(|znak| format!("{} × {}", glif(glify, znak), wynik.liczba(znak)))
+            .collect::<Vec<_>>()
+            .join(", "),
+        false,
+    );
+    osadzenie
+}
+
+/* # opis słowny */
+
+const fn klucz(znak: Znak) -> &'static str {
+    match znak {
+        Znak::Słońce => "wynik.słońce",
+        Znak::Księżyc => "wynik.księżyc",
+        Znak::Skaza => "wynik.skaza",
+    }
+}
+
+fn ścianka_słownie(mowa: &Mowa, ścianka: &[Znak]) -> String {
+    if ścianka.is_empty() {
+        return mowa.tekst("wynik.pusta");
+    }
+    ścianka
+        .iter()
+        .map(|znak| {
+            // pierwsza forma to mianownik liczby pojedynczej
+            let formy = mowa.tekst(klucz(*znak));
+            formy.split('|').next().unwrap_or_default().to_owned()
+        })
+        .collect::<Vec<_>>()
+        .join(&mowa.tekst("wynik.spójnik"))
+}
+
+/// wynik opisany słowami, czytelny dla czytników ekranu.
+pub fn opis(mowa: &Mowa, wynik: &Wynik) -> String {
+    let mut zdania = vec![tytuł(mowa, wynik)];
+    zdania.extend(wynik.grupy.iter().map(|grupa| {
+        let kości = match grupa.ścianki.as_slice() {
+            [] => mowa.tekst("wynik.brak_kości"),
+            ścianki => ścianki
+                .iter()
+                .map(|ścianka| ścianka_słownie(mowa, ścianka))
+                .collect::<Vec<_>>()
+                .join(", "),
+        };
+        format!("{}: {kości}", nazwa_grupy(mowa, grupa))
+    }));
+
+    let mut razem = vec![
+        mowa.liczebnik(klucz(Znak::Słońce), wynik.liczba(Znak::Słońce)),
+        mowa.liczebnik(klucz(Znak::Księżyc), wynik.liczba(Znak::Księżyc)),
+    ];
+    // skazy mogą wypaść na każdej kości, również wzorca własnego i pomocnika
+    let źródła = [
+        (RodzajKości::Wzorzec, "wynik.z_wzorca"),
+        (RodzajKości::Narzędzie, "wynik.z_narzędzia"),
+        (RodzajKości::Broń, "wynik.z_broni"),
+        (RodzajKości::Pomoc, "wynik.z_pomocy"),
+    ];
+    for (rodzaj, źródło) in źródła {
+        let skazy = wynik
+            .grupy
+            .iter()
+            .filter(|grupa| grupa.rodzaj == rodzaj)
+            .flat_map(|grupa| &grupa.ścianki)
+            .flatten()
+            .filter(|znak| **znak == Znak::Skaza)
+            .count();
+        if skazy > 0 {
+            razem.push(format!(
+                "{} {}",
+                mowa.liczebnik(klucz(Znak::Skaza), skazy),
+                mowa.tekst(źródło)
+            ));
+        }
+    }
+    if wynik.osłonięte > 0 {
+        let skazy = mowa.liczebnik(klucz(Znak::Skaza), wynik.osłonięte);
+        razem.push(mowa.wstaw("wynik.osłonięte", &[("skazy", &skazy)]));
+    }
+    zdania.push(mowa.wstaw("wynik.razem_opis", &[("suma", &razem.join(", "))]));
+    zdania.join(". ") + "."
+}
+
+/* # szablony gildii */
+
+/// wydźwięki wyników, dla których gildia może ustawić tabelę opisów.
+pub const WYDŹWIĘKI: &[&str] = &[
+    "miażdżący",
+    "słońca",
+    "remis",
+    "księżyce",
+    "zniszczenie",
+    "trwałość",
+];
+
+/// wydźwięk próby lub bitwy. miażdżący wynik nie ma ani jednego księżyca ani skazy.
+fn wydźwięk(wynik: &Wynik) -> &'static str {
+    let (słońca, księżyce) = (wynik.liczba(Znak::Słońce), wynik.liczba(Znak::Księżyc));
+    match słońca.cmp(&księżyce) {
+        Ordering::Greater if księżyce == 0 && wynik.liczba(Znak::Skaza) == 0 => "miażdżący",
+        Ordering::Greater => "słońca",
+        Ordering::Equal => "remis",
+        Ordering::Less => "księżyce",
+    }
+}
+
+/// treść z dołączonym losowym opisem wydźwięku, o ile gildia ustawiła ich tabelę.
+pub fn z_opisem(
+    mowa: &Mowa,
+    treść: String,
+    wydźwięk: &str,
+    wartości: &[(&str, &str)],
+) -> String {
+    let opis = mowa.opis(wydźwięk, wartości).map(|opis| format!("*{opis}*"));
+    [treść].into_iter().chain(opis).filter(|linia| !linia.is_empty()).join("\n")
+}
+
+/// odpowiedź z wynikiem rzutu, wyświetlonym glifami gildii nadawcy lub słowami,
+/// jeśli gracz wybrał wyniki opisowe. treść pochodzi z szablonu `wynik.treść`,
+/// w katalogu pustego.
+pub async fn odpowiedź(ctx: &Context, nadawca: &Nadawca, wynik: &Wynik) -> Odpowiedź {
+    let ustawienia = ustawienia::gildii(ctx, nadawca).await;
+    let opisowe = ustawienia::gracza(ctx, nadawca).await.opisowe;
+    let mowa = ustawienia::mowa(ctx, nadawca).await;
+    let obraz = ustawienia
+        .obrazy
+        .then(|| obrazy::png(wynik))
+        .and_then(|obraz| {
+            obraz
+                .map_err(|why| tracing::error!(błąd = %why, "nie udało się narysować rzutu"))
+                .ok()
+        });
+
+    let glify = &ustawienia.glify;
+    let kości = wynik
+        .grupy
+        .iter()
+        .flat_map(|grupa| {
+            grupa
+                .ścianki
+                .iter()
+                .map(|ścianka| kość(glify, grupa.rodzaj, ścianka))
+        })
+        .join(" ");
+    let [słońca, księżyce, skazy] =
+        [Znak::Słońce, Znak::Księżyc, Znak::Skaza].map(|znak| wynik.liczba(znak).to_string());
+    let suma = suma(glify, &mowa, wynik);
+    let wartości = [
+        ("kości", kości.as_str()),
+        ("słońca", słońca.as_str()),
+        ("księżyce", księżyce.as_str()),
+        ("skazy", skazy.as_str()),
+        ("suma", suma.as_str()),
+    ];
+
+    let mut treść = mowa.wstaw("wynik.treść", &wartości);
+    if opisowe {
+        treść = [treść, opis(&mowa, wynik)]
+            .into_iter()
+            .filter(|linia| !linia.is_empty())
+            .join("\n");
+    }
+    Odpowiedź {
+        treść: z_opisem(&mowa, treść, wydźwięk(wynik), &wartości),
+        osadzenie: (!opisowe).then(|| osadzenie(glify, &mowa, wynik)),
+        obraz,
+        przyciski: None,
+    }
+}
+
+/// odpowiedź z wynikiem po przerzucie, poprzedzona ściankami przerzuconych kości przed
+/// przerzutem i po nim.
+pub async fn przerzut(ctx: &Context, nadawca: &Nadawca, wynik: &Wynik) -> Odpowiedź {
+    let glify = ustawienia::gildii(ctx, nadawca).await.glify;
+    let opisowe = ustawienia::gracza(ctx, nadawca).await.opisowe;
+    let mowa = ustawienia::mowa(ctx, nadawca).await;
+    let wygląd = |rodzaj: RodzajKości, ścianka: &[Znak]| {
+        if opisowe {
+            ścianka_słownie(&mowa, ścianka)
+        } else {
+            kość(&glify, rodzaj, ścianka)
+        }
+    };
+    let przerzucone = wynik
+        .przerzuty
+        .last()
+        .into_iter()
+        .flatten()
+        .map(|przerzut| {
+            mowa.wstaw(
+                "rzut.przerzucona",
+                &[
+                    ("numer", &przerzut.numer.to_string()),
+                    ("przed", &wygląd(przerzut.rodzaj, &przerzut.przed)),
+                    ("po", &wygląd(przerzut.rodzaj, &przerzut.po)),
+                ],
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut odpowiedź = odpowiedź(ctx, nadawca, wynik).await;
+    odpowiedź.treść = [mowa.tekst("rzut.przerzucono")]
+        .into_iter()
+        .chain(przerzucone)
+        .chain([odpowiedź.treść])
+        .filter(|linia| !linia.is_empty())
+        .join("\n");
+    odpowiedź
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{reguły::Reguły, ustawienia::Język, zug};
+
+    #[test]
+    fn opis_skaz() {
+        let reguły = Reguły::z_tekstu(
+            r#"
+            [wzorce."własne".olbrzym]
+            "kość" = ["X", "X", "X", "X", "X", "X"]
+            "#,
+        )
+        .unwrap();
+        let olbrzym = zug::Wzorzec::Własny("olbrzym".to_owned());
+        let wynik = zug::próba(
+            &reguły,
+            &olbrzym,
+            zug::Fach::Zielony,
+            &[],
+            [("kumpel", &olbrzym)].into_iter(),
+            0,
+        )
+        .unwrap();
+
+        let opis = opis(&Mowa::from(Język::Polski), &wynik);
+        assert!(opis.contains("4 skazy ze wzorca"), "{opis}");
+        assert!(opis.contains("1 skaza z pomocy"), "{opis}");
+    }
+}
@@ -1,43 +1,74 @@
-use crate::parser::{Arg, InvalidArgument};
-use itertools::Itertools;
+use crate::{
+    error::Błąd,
+    komendy,
+    parser::Arg,
+    reguły::{Kość, Reguły, ZasadaCechy, NAJWIĘCEJ_KOŚCI},
+};
 use rand::{
     distributions::{Bernoulli, Distribution},
     seq::SliceRandom,
     thread_rng,
 };
-use serenity::framework::standard::CommandResult;
-use std::fmt;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, fmt, iter};
 use strsim::damerau_levenshtein as dist;
 
-const ZNAKI: &str = "\u{1d6af}\u{1d6b2}";
-const SUN: &str = "\u{1d6af}";
-const MUN: &str = "\u{1d6b2}";
-
 /* # wzorzec */
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum Wzorzec {
     Chojrak,
     Szelma,
+    /// wzorzec z reguł gildii, według nazwy
+    Własny(String),
 }
 
 impl Wzorzec {
-    pub fn try_parse(arg: &Arg) -> Option<Self> {
-        match arg {
-            Arg::Short('c', _) => Some(Self::Chojrak),
-            Arg::Long(word, _) if dist(word, "chojrak") < 4 || dist(word, "chojraczka") < 4 => {
-                Some(Self::Chojrak)
-            }
-            Arg::Short('w', _) => Some(Self::Szelma),
-            Arg::Long(word, _) if dist(word, "szelma") < 4 => Some(Self::Chojrak),
-            _ => None,
-        }
+    fn try_parse_podstawowy(arg: &Arg) -> Option<Self> {
+        [
+            (komendy::CHOJRAK, Self::Chojrak),
+            (komendy::SZELMA, Self::Szelma),
+        ]
+        .into_iter()
+        .find_map(|(opcja, wzorzec)| opcja.pasuje(arg).then_some(wzorzec))
+    }
+
+    /// wzorzec podstawowy, który wybrałaby podana nazwa.
+    pub fn podstawowy(nazwa: &str) -> Option<Self> {
+        Self::try_parse_podstawowy(&Arg::Long(nazwa.to_owned(), Vec::new()))
     }
 
-    pub const fn die(self) -> [&'static str; 6] {
+    /// wzorzec podstawowy lub własny o nazwie albo aliasie najbliższym podanej.
+    pub fn try_parse(arg: &Arg, reguły: &Reguły) -> Option<Self> {
+        Self::try_parse_podstawowy(arg).or_else(|| {
+            let Arg::Long(słowo, _) = arg else {
+                return None;
+            };
+            reguły
+                .wzorce
+                .własne
+                .iter()
+                .flat_map(|(nazwa, wzorzec)| {
+                    iter::once(nazwa)
+                        .chain(&wzorzec.aliasy)
+                        .map(move |inna| (dist(słowo, inna), nazwa))
+                })
+                .filter(|(odległość, _)| *odległość < komendy::TOLERANCJA_WARTOŚCI)
+                .min()
+                .map(|(_, nazwa)| Self::Własny(nazwa.clone()))
+        })
+    }
+
+    pub fn kość<'a>(&self, reguły: &'a Reguły) -> Result<&'a Kość, Błąd> {
         match self {
-            Self::Chojrak => [SUN, SUN, SUN, MUN, MUN, ""],
-            Self::Szelma => [SUN, SUN, MUN, MUN, MUN, ""],
+            Self::Chojrak => Ok(&reguły.wzorce.chojrak),
+            Self::Szelma => Ok(&reguły.wzorce.szelma),
+            Self::Własny(nazwa) => reguły
+                .wzorce
+                .własne
+                .get(nazwa)
+                .map(|wzorzec| &wzorzec.kość)
+                .ok_or_else(|| Błąd::NieznanyWzorzec(nazwa.clone())),
         }
     }
 }
@@ -47,9 +78,10 @@ impl fmt::Display for Wzorzec {
         write!(
             f,
             "{}",
-            match *self {
+            match self {
                 Self::Chojrak => "Chojrak",
                 Self::Szelma => "Szelma",
+                Self::Własny(nazwa) => nazwa,
             }
         )
     }
@@ -57,109 +89,123 @@ impl fmt::Display for Wzorzec {
 
 /* # szkolenie */
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub enum Fach {
+    #[default]
     Zielony,
     Szkolony,
     Biegły,
 }
 
 impl Fach {
-    const fn dice(self) -> usize {
+    const fn kości(self, reguły: &Reguły) -> usize {
         match self {
-            Self::Zielony => 4,
-            Self::Szkolony => 5,
-            Self::Biegły => 6,
+            Self::Zielony => reguły.fachy.zielony,
+            Self::Szkolony => reguły.fachy.szkolony,
+            Self::Biegły => reguły.fachy.biegły,
         }
     }
 
     pub fn try_parse(arg: &Arg) -> Option<Self> {
-        match arg {
-            Arg::Short('s', _) => Some(Self::Szkolony),
-            Arg::Short('b', _) => Some(Self::Biegły),
-            Arg::Long(word, _) if dist(word, "znakomita") < 4 => Some(Self::Szkolony),
-            Arg::Long(word, _) if dist(word, "przyzwoita") < 4 => Some(Self::Biegły),
-            _ => None,
-        }
-    }
-}
-
-impl Default for Fach {
-    fn default() -> Self {
-        Self::Zielony
+        [
+            (komendy::SZKOLONY, Self::Szkolony),
+            (komendy::BIEGŁY, Self::Biegły),
+        ]
+        .into_iter()
+        .find_map(|(opcja, fach)| opcja.pasuje(arg).then_some(fach))
     }
 }
 
 /* # narzędzia */
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Narzędzie {
     Kiepskie,
+    #[default]
     Przyzwoite,
     Znakomite,
 }
 
 impl Narzędzie {
+    /// jakości od najgorszej.
+    const JAKOŚCI: [Self; 3] = [Self::Kiepskie, Self::Przyzwoite, Self::Znakomite];
+
     pub fn try_parse(arg: &Arg) -> Option<Self> {
-        match arg {
-            Arg::Short('z', _) => Some(Self::Znakomite),
-            Arg::Short('p', _) => Some(Self::Przyzwoite),
-            Arg::Short('k', _) => Some(Self::Kiepskie),
-            Arg::Long(word, _) if dist(word, "znakomita") < 4 => Some(Self::Znakomite),
-            Arg::Long(word, _) if dist(word, "przyzwoita") < 4 => Some(Self::Przyzwoite),
-            Arg::Long(word, _) if dist(word, "kiepska") < 4 => Some(Self::Kiepskie),
-            _ => None,
+        [
+            (komendy::ZNAKOMITA, Self::Znakomite),
+            (komendy::PRZYZWOITA, Self::Przyzwoite),
+            (komendy::KIEPSKA, Self::Kiepskie),
+        ]
+        .into_iter()
+        .find_map(|(opcja, narzędzie)| opcja.pasuje(arg).then_some(narzędzie))
+    }
+
+    const fn kość(self, reguły: &Reguły) -> &Kość {
+        match self {
+            Self::Kiepskie => &reguły.narzędzia.kiepskie,
+            Self::Przyzwoite => &reguły.narzędzia.przyzwoite,
+            Self::Znakomite => &reguły.narzędzia.znakomite,
         }
     }
 
-    const fn die(self) -> [&'static str; 4] {
+    pub const fn nazwa(self) -> &'static str {
         match self {
-            Self::Kiepskie => ["XX", "X", "", ""],
-            Self::Przyzwoite => ["X", "X", "", ""],
-            Self::Znakomite => ["X", "", "", ""],
+            Self::Kiepskie => "kiepskie",
+            Self::Przyzwoite => "przyzwoite",
+            Self::Znakomite => "znakomite",
         }
     }
 
-    fn decay(self) -> CommandResult<f64> {
-        Ok(f64::from(u8::try_from(
-            self.die()
-                .into_iter()
-                .filter(|sigils| sigils.is_empty())
-                .count(),
-        )?) / 4.0)
+    /// jakość lepsza lub gorsza o podaną liczbę stopni, w granicach dostępnych jakości.
+    fn przesuń(self, stopnie: isize) -> Self {
+        let indeks = Self::JAKOŚCI
+            .iter()
+            .position(|jakość| *jakość == self)
+            .unwrap_or_default()
+            .saturating_add_signed(stopnie)
+            .min(Self::JAKOŚCI.len() - 1);
+        Self::JAKOŚCI[indeks]
     }
-}
 
-impl Default for Narzędzie {
-    fn default() -> Self {
-        Self::Przyzwoite
+    fn decay(self, reguły: &Reguły) -> Result<f64, Błąd> {
+        let ścianki = self.kość(reguły).ścianki();
+        let puste = ścianki.iter().filter(|sigils| sigils.is_empty()).count();
+        let niepoprawne = |why: std::num::TryFromIntError| Błąd::NiepoprawneReguły(why.to_string());
+        let puste = u32::try_from(puste).map_err(niepoprawne)?;
+        let wszystkie = u32::try_from(ścianki.len()).map_err(niepoprawne)?;
+        Ok(f64::from(puste) / f64::from(wszystkie))
     }
 }
 
 /* # broń */
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub enum Zasięg {
     Biała,
+    Drzewcowa,
     Miotająca,
 }
 
 impl Zasięg {
-    fn try_parse(arg: &Arg) -> Option<Self> {
-        match arg {
-            Arg::Short('z', params) => params.last().and_then(|s| match s {
-                x if x == "b" => Some(Self::Biała),
-                x if x == "m" || x == "z" => Some(Self::Miotająca),
-                _ => None,
-            }),
+    fn parse_helper(params: &[String]) -> Option<Self> {
+        params.last().and_then(|s| match s {
+            x if x == "b" || dist(x, "biała") < 3 => Some(Self::Biała),
+            x if x == "d" || dist(x, "drzewcowa") < 3 => Some(Self::Drzewcowa),
+            x if x == "m" || x == "z" || dist(x, "miotająca") < 3 => Some(Self::Miotająca),
             _ => None,
-        }
+        })
+    }
+
+    fn try_parse(arg: &Arg) -> Option<Self> {
+        komendy::ZASIĘG.dopasuj(arg).and_then(Self::parse_helper)
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub enum Waga {
     Lekka,
+    Średnia,
     Ciężka,
 }
 
@@ -167,132 +213,721 @@ impl Waga {
     fn parse_helper(params: &[String]) -> Option<Self> {
         params.last().and_then(|s| match s {
             x if x == "l" || dist(x, "lekka") < 3 => Some(Self::Lekka),
+            x if x == "ś" || x == "s" || dist(x, "średnia") < 3 => Some(Self::Średnia),
             x if x == "c" || dist(x, "ciężka") < 3 => Some(Self::Ciężka),
             _ => None,
         })
     }
 
     fn try_parse(arg: &Arg) -> Option<Self> {
-        match arg {
-            Arg::Short('z', params) => Self::parse_helper(params),
-            Arg::Long(p, params) if dist(p, "zasięg") < 3 => Self::parse_helper(params),
-            _ => None,
+        komendy::WAGA.dopasuj(arg).and_then(Self::parse_helper)
+    }
+}
+
+/// cechy szczególne broni, zmieniające jej kość według reguł.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Cecha {
+    Dwuręczna,
+    Palna,
+    Improwizowana,
+    Mistrzowska,
+}
+
+impl Cecha {
+    const WSZYSTKIE: [Self; 4] = [
+        Self::Dwuręczna,
+        Self::Palna,
+        Self::Improwizowana,
+        Self::Mistrzowska,
+    ];
+
+    pub const fn nazwa(self) -> &'static str {
+        match self {
+            Self::Dwuręczna => "dwuręczna",
+            Self::Palna => "palna",
+            Self::Improwizowana => "improwizowana",
+            Self::Mistrzowska => "mistrzowska",
+        }
+    }
+
+    fn parse_helper(param: &str) -> Option<Self> {
+        let mut znaki = param.chars();
+        let skrót = znaki.next().filter(|_| znaki.next().is_none());
+        Self::WSZYSTKIE.into_iter().find(|cecha| {
+            skrót.is_some() && skrót == cecha.nazwa().chars().next()
+                || dist(param, cecha.nazwa()) < 3
+        })
+    }
+
+    const fn zasada(self, reguły: &Reguły) -> &ZasadaCechy {
+        match self {
+            Self::Dwuręczna => &reguły.cechy.dwuręczna,
+            Self::Palna => &reguły.cechy.palna,
+            Self::Improwizowana => &reguły.cechy.improwizowana,
+            Self::Mistrzowska => &reguły.cechy.mistrzowska,
         }
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Broń {
     waga: Waga,
     zasięg: Zasięg,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    cechy: BTreeSet<Cecha>,
 }
 
 impl Broń {
-    pub fn try_parse(args: &[Arg]) -> Result<Self, InvalidArgument> {
-        args.iter().filter_map(Waga::try_parse).last().map_or_else(
-            || {
-                Err(InvalidArgument::new(
-                    "nie podano argumentu wagi.".to_owned(),
-                ))
-            },
-            |waga| {
-                args.iter()
-                    .filter_map(Zasięg::try_parse)
-                    .last()
-                    .map_or_else(
-                        || {
-                            Err(InvalidArgument::new(
-                                "nie podano argumentu zasięgu.".to_owned(),
-                            ))
-                        },
-                        |zasięg| Ok(Self { waga, zasięg }),
-                    )
-            },
-        )
+    pub fn try_parse(args: &[Arg]) -> Result<Self, Błąd> {
+        let waga = args
+            .iter()
+            .filter_map(Waga::try_parse)
+            .next_back()
+            .ok_or(Błąd::BrakArgumentu("waga"))?;
+        let zasięg = args
+            .iter()
+            .filter_map(Zasięg::try_parse)
+            .next_back()
+            .ok_or(Błąd::BrakArgumentu("zasięg"))?;
+        // polecenia discorda podają wszystkie cechy w jednej wartości
+        let cechy = args
+            .iter()
+            .filter_map(|arg| komendy::CECHY.dopasuj(arg))
+            .flatten()
+            .flat_map(|param| param.split_whitespace())
+            .map(|param| {
+                Cecha::parse_helper(param)
+                    .ok_or_else(|| Błąd::NiepoprawnaWartość("cecha", param.to_owned()))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            waga,
+            zasięg,
+            cechy,
+        })
+    }
+
+    /// jakość narzędzia, którego kością rzuca się za broń, zmieniona przez jej cechy.
+    pub fn narzędzie(&self, reguły: &Reguły) -> Narzędzie {
+        let zasięgi = match self.waga {
+            Waga::Lekka => reguły.bronie.lekka,
+            Waga::Średnia => reguły.bronie.średnia,
+            Waga::Ciężka => reguły.bronie.ciężka,
+        };
+        let narzędzie = match self.zasięg {
+            Zasięg::Biała => zasięgi.biała,
+            Zasięg::Drzewcowa => zasięgi.drzewcowa,
+            Zasięg::Miotająca => zasięgi.miotająca,
+        };
+        narzędzie.przesuń(self.zasady(reguły).map(|zasada| zasada.jakość).sum())
+    }
+
+    /// kość broni. cecha z własną kością zastępuje kość narzędzia.
+    pub fn kość<'a>(&self, reguły: &'a Reguły) -> &'a Kość {
+        self.cechy
+            .iter()
+            .filter_map(|cecha| cecha.zasada(reguły).kość.as_ref())
+            .next_back()
+            .unwrap_or_else(|| self.narzędzie(reguły).kość(reguły))
+    }
+
+    /// liczba kości broni w bitwie.
+    pub fn kości(&self, reguły: &Reguły) -> usize {
+        1 + self.zasady(reguły).map(|zasada| zasada.kości).sum::<usize>()
+    }
+
+    fn zasady<'a>(&'a self, reguły: &'a Reguły) -> impl Iterator<Item = &'a ZasadaCechy> {
+        self.cechy.iter().map(|cecha| cecha.zasada(reguły))
     }
 
-    pub fn die(self) -> [&'static str; 4] {
-        Narzędzie::from(self).die()
+    pub fn cechy(&self) -> impl Iterator<Item = Cecha> + '_ {
+        self.cechy.iter().copied()
     }
 
-    pub const fn waga_str(self) -> &'static str {
+    /// ręce zajmowane przez broń.
+    fn ręce(&self) -> usize {
+        if self.cechy.contains(&Cecha::Dwuręczna) {
+            2
+        } else {
+            1
+        }
+    }
+
+    pub const fn waga_str(&self) -> &'static str {
         match self.waga {
             Waga::Lekka => "lekka",
+            Waga::Średnia => "średnia",
             Waga::Ciężka => "ciężka",
         }
     }
 
-    pub const fn zasięg_str(self) -> &'static str {
+    pub const fn zasięg_str(&self) -> &'static str {
         match self.zasięg {
             Zasięg::Biała => "biała",
+            Zasięg::Drzewcowa => "drzewcowa",
             Zasięg::Miotająca => "miotająca",
         }
     }
 }
 
-impl From<Broń> for Narzędzie {
-    fn from(broń: Broń) -> Self {
-        match (broń.waga, broń.zasięg) {
-            (Waga::Lekka, Zasięg::Miotająca) => Self::Kiepskie,
-            (Waga::Ciężka, Zasięg::Miotająca) | (Waga::Lekka, Zasięg::Biała) => {
-                Self::Przyzwoite
-            }
-            (Waga::Ciężka, Zasięg::Biała) => Self::Znakomite,
+/// nowa waga lub zasięg istniejącej broni, pozostałe wartości broni zostają bez zmian.
+#[derive(Clone, Copy)]
+pub struct ZmianaBroni {
+    waga: Option<Waga>,
+    zasięg: Option<Zasięg>,
+}
+
+impl ZmianaBroni {
+    pub fn try_parse(args: &[Arg]) -> Result<Self, Błąd> {
+        let waga = args.iter().filter_map(Waga::try_parse).next_back();
+        let zasięg = args.iter().filter_map(Zasięg::try_parse).next_back();
+        if waga.is_none() && zasięg.is_none() {
+            return Err(Błąd::BrakArgumentu("waga"));
+        }
+        Ok(Self { waga, zasięg })
+    }
+
+    pub fn zastosuj(self, broń: &mut Broń) {
+        broń.waga = self.waga.unwrap_or(broń.waga);
+        broń.zasięg = self.zasięg.unwrap_or(broń.zasięg);
+    }
+}
+
+/// sprawdza, czy bronie używane naraz mieszczą się w rękach gracza oraz limicie broni ciężkich.
+pub fn sprawdź_uzbrojenie<'a, I>(reguły: &Reguły, bronie: I) -> Result<(), Błąd>
+where
+    I: Iterator<Item = &'a Broń>,
+{
+    let (ręce, ciężkie) = bronie.fold((0, 0), |(ręce, ciężkie), broń| {
+        let ciężka = matches!(broń.waga, Waga::Ciężka);
+        (ręce + broń.ręce(), ciężkie + usize::from(ciężka))
+    });
+    if ręce > reguły.uzbrojenie.ręce {
+        return Err(Błąd::Uzbrojenie("ręce", reguły.uzbrojenie.ręce));
+    }
+    if ciężkie > reguły.uzbrojenie.ciężkie {
+        return Err(Błąd::Uzbrojenie("ciężkie", reguły.uzbrojenie.ciężkie));
+    }
+    Ok(())
+}
+
+/* # osłony */
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum RodzajOsłony {
+    Pancerz,
+    Tarcza,
+}
+
+impl RodzajOsłony {
+    fn parse_helper(params: &[String]) -> Option<Self> {
+        params.last().and_then(|s| match s {
+            x if x == "p" || dist(x, "pancerz") < 3 => Some(Self::Pancerz),
+            x if x == "t" || dist(x, "tarcza") < 3 => Some(Self::Tarcza),
+            _ => None,
+        })
+    }
+
+    fn try_parse(arg: &Arg) -> Option<Self> {
+        komendy::RODZAJ.dopasuj(arg).and_then(Self::parse_helper)
+    }
+}
+
+/// pancerz lub tarcza. założona osłona zatrzymuje skazy w bitwie, a w zaniku traci trwałość.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Osłona {
+    rodzaj: RodzajOsłony,
+    pub jakość: Narzędzie,
+    pub trwałość: usize,
+}
+
+impl Osłona {
+    pub fn try_parse(args: &[Arg]) -> Result<Self, Błąd> {
+        let rodzaj = args
+            .iter()
+            .filter_map(RodzajOsłony::try_parse)
+            .next_back()
+            .ok_or(Błąd::BrakArgumentu("rodzaj"))?;
+        // trwałość podawana jest liczbą, jak w zaniku
+        let trwałość = args
+            .iter()
+            .find_map(|arg| match arg {
+                Arg::Plain(trwałość) => trwałość.parse::<usize>().ok(),
+                _ => None,
+            })
+            .ok_or(Błąd::BrakArgumentu("trwałość"))?;
+        if trwałość == 0 {
+            return Err(Błąd::NiepoprawnaWartość("trwałość", trwałość.to_string()));
+        }
+        Ok(Self {
+            rodzaj,
+            jakość: args
+                .iter()
+                .filter_map(Narzędzie::try_parse)
+                .next_back()
+                .unwrap_or_default(),
+            trwałość,
+        })
+    }
+
+    /// liczba skaz, które osłona zatrzymuje w bitwie.
+    pub const fn skazy(&self, reguły: &Reguły) -> usize {
+        let jakości = match self.rodzaj {
+            RodzajOsłony::Pancerz => reguły.osłony.pancerz,
+            RodzajOsłony::Tarcza => reguły.osłony.tarcza,
+        };
+        match self.jakość {
+            Narzędzie::Kiepskie => jakości.kiepskie,
+            Narzędzie::Przyzwoite => jakości.przyzwoite,
+            Narzędzie::Znakomite => jakości.znakomite,
         }
     }
+
+    pub const fn rodzaj_str(&self) -> &'static str {
+        match self.rodzaj {
+            RodzajOsłony::Pancerz => "pancerz",
+            RodzajOsłony::Tarcza => "tarcza",
+        }
+    }
+}
+
+/* # wyniki */
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Znak {
+    Słońce,
+    Księżyc,
+    Skaza,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RodzajKości {
+    Wzorzec,
+    Narzędzie,
+    Broń,
+    /// kości wzorca gracza pomagającego w próbie
+    Pomoc,
+}
+
+/// kości jednego pochodzenia wraz z wyrzuconymi ściankami. kość pozwala je przerzucić.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Grupa {
+    pub rodzaj: RodzajKości,
+    pub nazwa: String,
+    pub kość: Kość,
+    pub ścianki: Vec<Vec<Znak>>,
+}
+
+/// przerzucona kość: jej numer w wyniku oraz ścianki przed przerzutem i po nim.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Przerzut {
+    pub numer: usize,
+    pub rodzaj: RodzajKości,
+    pub przed: Vec<Znak>,
+    pub po: Vec<Znak>,
 }
 
-/* to be sorted */
+/// wynik rzutu w postaci, z której korzystają wszystkie sposoby jego wyświetlenia.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Wynik {
+    pub rzut: String,
+    pub grupy: Vec<Grupa>,
+    /// skazy zatrzymane przez osłony
+    #[serde(default)]
+    pub osłonięte: usize,
+    /// skazy, które mogą zatrzymać osłony, również po przerzucie
+    #[serde(default)]
+    pub osłony: usize,
+    /// kolejne przerzuty wyniku, od najstarszego
+    #[serde(default)]
+    pub przerzuty: Vec<Vec<Przerzut>>,
+}
+
+impl Wynik {
+    /// liczba wyrzuconych znaków. skazy zatrzymane przez osłony nie są liczone.
+    pub fn liczba(&self, znak: Znak) -> usize {
+        let wyrzucone = self.wyrzucone(znak);
+        match znak {
+            Znak::Skaza => wyrzucone - self.osłonięte,
+            Znak::Słońce | Znak::Księżyc => wyrzucone,
+        }
+    }
+
+    fn wyrzucone(&self, znak: Znak) -> usize {
+        self.grupy
+            .iter()
+            .flat_map(|grupa| &grupa.ścianki)
+            .flatten()
+            .filter(|wyrzucony| **wyrzucony == znak)
+            .count()
+    }
+
+    /// osłony nie zatrzymają więcej skaz, niż wyrzucono.
+    fn osłoń(&mut self) {
+        self.osłonięte = self.osłony.min(self.wyrzucone(Znak::Skaza));
+    }
+
+    /// numery kości, numerowanych od jedynki w kolejności wyświetlania, na których wypadł znak.
+    pub fn numery(&self, znak: Znak) -> Vec<usize> {
+        self.grupy
+            .iter()
+            .flat_map(|grupa| &grupa.ścianki)
+            .zip(1..)
+            .filter(|(ścianka, _)| ścianka.contains(&znak))
+            .map(|(_, numer)| numer)
+            .collect()
+    }
 
-#[allow(clippy::match_bool)] // i think this is more readable
-fn encapsulate(s: &str, i: bool) -> String {
-    match i {
-        true => format!("({s})"),
-        false => format!("[{s}]"),
+    /// przerzuca kości o podanych numerach i zapisuje przerzut w historii wyniku.
+    pub fn przerzuć(&mut self, numery: &[usize]) -> Result<&[Przerzut], Błąd> {
+        let kości = self.grupy.iter().map(|grupa| grupa.ścianki.len()).sum::<usize>();
+        if let Some(numer) = numery.iter().find(|numer| !(1..=kości).contains(*numer)) {
+            return Err(Błąd::NiepoprawnaWartość("kość", numer.to_string()));
+        }
+        let mut rng = thread_rng();
+        let mut przerzut = Vec::new();
+        let wszystkie = self.grupy.iter_mut().flat_map(|grupa| {
+            let Grupa {
+                rodzaj, kość, ścianki, ..
+            } = grupa;
+            let (rodzaj, kość) = (*rodzaj, &*kość);
+            ścianki.iter_mut().map(move |ścianka| (rodzaj, kość, ścianka))
+        });
+        for ((rodzaj, kość, ścianka), numer) in wszystkie.zip(1..) {
+            if !numery.contains(&numer) {
+                continue;
+            }
+            let po = kość.ścianki().choose(&mut rng).cloned().unwrap_or_default();
+            przerzut.push(Przerzut {
+                numer,
+                rodzaj,
+                przed: std::mem::replace(ścianka, po.clone()),
+                po,
+            });
+        }
+        self.osłoń();
+        self.przerzuty.push(przerzut);
+        Ok(self.przerzuty.last().map_or(&[], Vec::as_slice))
     }
 }
 
-pub fn próba(wzór: Wzorzec, fach: Fach, maybe_narzędzie: Option<Narzędzie>) -> String {
+fn rzuć(kość: &Kość, ile: usize) -> Vec<Vec<Znak>> {
     let mut rng = thread_rng();
-    let mut results = (0..fach.dice())
-        .filter_map(|_| wzór.die().choose(&mut rng).copied())
-        .map(|s| encapsulate(s, false))
-        .collect::<Vec<String>>();
-    if let Some(narzędzie) =
-        maybe_narzędzie.and_then(|narzędzie| narzędzie.die().choose(&mut rng).copied())
-    {
-        results.push(encapsulate(narzędzie, true));
-    };
-    let mut concise = results.join("").chars().sorted().collect::<String>();
-    concise.retain(|c| ZNAKI.contains(c));
-    let results = results.join(" ");
-    format!("{results}   =>>   {concise}")
+    (0..ile)
+        .filter_map(|_| kość.ścianki().choose(&mut rng).cloned())
+        .collect()
+}
+
+/* # pule */
+
+/// kości jednego pochodzenia, którymi rzut by rzucił.
+#[derive(Clone, Debug)]
+pub struct Pula<'a> {
+    pub rodzaj: RodzajKości,
+    pub nazwa: String,
+    /// jakość narzędzia, od której zależy kość. wzorzec nie ma narzędzia.
+    pub narzędzie: Option<Narzędzie>,
+    pub kość: &'a Kość,
+    pub ile: usize,
 }
 
-pub fn bitwa<I>(wzór: Wzorzec, bronie: I, modyfikator: isize) -> String
+impl Pula<'_> {
+    fn rzuć(&self) -> Grupa {
+        Grupa {
+            rodzaj: self.rodzaj,
+            nazwa: self.nazwa.clone(),
+            kość: self.kość.clone(),
+            ścianki: rzuć(self.kość, self.ile),
+        }
+    }
+}
+
+/// kości wzorca po modyfikatorze, nie mniej niż zero.
+const fn kości_wzorca(kości: usize, modyfikator: isize) -> usize {
+    kości.saturating_add_signed(modyfikator)
+}
+
+/// pula nie może przekroczyć [`NAJWIĘCEJ_KOŚCI`], również po modyfikatorach gracza.
+fn sprawdź_pulę(pula: Vec<Pula>) -> Result<Vec<Pula>, Błąd> {
+    let ile = pula.iter().fold(0, |ile: usize, kości| ile.saturating_add(kości.ile));
+    if ile > NAJWIĘCEJ_KOŚCI {
+        return Err(Błąd::NiepoprawnaWartość("kości", ile.to_string()));
+    }
+    Ok(pula)
+}
+
+/// kości, którymi rzuciłaby próba. każde narzędzie dokłada swoją kość, a każdy pomocnik
+/// kości swojego wzorca.
+pub fn pula_próby<'a, 'b, I>(
+    reguły: &'a Reguły,
+    wzór: &Wzorzec,
+    fach: Fach,
+    narzędzia: &[Narzędzie],
+    pomocnicy: I,
+    modyfikator: isize,
+) -> Result<Vec<Pula<'a>>, Błąd>
 where
-    I: Iterator<Item = Broń>,
+    I: Iterator<Item = (&'b str, &'b Wzorzec)>,
 {
-    let mut rng = thread_rng();
-    let mut results = (0..(4 + modyfikator))
-        .filter_map(|_| wzór.die().choose(&mut rng).copied())
-        .map(|s| encapsulate(s, false))
-        .collect::<Vec<String>>();
-    for broń in bronie.filter_map(|broń| broń.die().choose(&mut rng).copied()) {
-        results.push(encapsulate(broń, true));
-    }
-    let mut concise = results.join("").chars().sorted().collect::<String>();
-    concise.retain(|c| ZNAKI.contains(c));
-    let results = results.join(" ");
-    format!("{results}   =>>   {concise}")
-}
-
-#[allow(clippy::match_bool)] // i think this is more readable
-pub fn zanik(durability: usize, quality: Narzędzie) -> CommandResult<String> {
-    match Bernoulli::new(quality.decay()?.powi(durability.try_into()?))?
-        .sample(&mut rand::thread_rng())
-    {
-        true => Ok("porażka! trwałość twojego sprzętu maleje. ".to_owned()),
-        false => Ok("sukces! twój sprzęt utrzymuje trwałość. ".to_owned()),
+    let mut pula = vec![Pula {
+        rodzaj: RodzajKości::Wzorzec,
+        nazwa: wzór.to_string(),
+        narzędzie: None,
+        kość: wzór.kość(reguły)?,
+        ile: kości_wzorca(fach.kości(reguły), modyfikator),
+    }];
+    pula.extend(narzędzia.iter().map(|narzędzie| Pula {
+        rodzaj: RodzajKości::Narzędzie,
+        nazwa: narzędzie.nazwa().to_owned(),
+        narzędzie: Some(*narzędzie),
+        kość: narzędzie.kość(reguły),
+        ile: 1,
+    }));
+    for (nazwa, wzór) in pomocnicy {
+        pula.push(Pula {
+            rodzaj: RodzajKości::Pomoc,
+            nazwa: nazwa.to_owned(),
+            narzędzie: None,
+            kość: wzór.kość(reguły)?,
+            ile: reguły.pomoc.kości,
+        });
+    }
+    sprawdź_pulę(pula)
+}
+
+/// kości, którymi rzuciłaby bitwa. modyfikator zmienia liczbę kości wzorca.
+pub fn pula_bitwy<'a, 'b, I>(
+    reguły: &'a Reguły,
+    wzór: &Wzorzec,
+    bronie: I,
+    modyfikator: isize,
+) -> Result<Vec<Pula<'a>>, Błąd>
+where
+    I: Iterator<Item = (&'b str, &'b Broń)>,
+{
+    let mut pula = vec![Pula {
+        rodzaj: RodzajKości::Wzorzec,
+        nazwa: wzór.to_string(),
+        narzędzie: None,
+        kość: wzór.kość(reguły)?,
+        ile: kości_wzorca(reguły.bitwa.kości, modyfikator),
+    }];
+    pula.extend(bronie.map(|(nazwa, broń)| Pula {
+        rodzaj: RodzajKości::Broń,
+        nazwa: nazwa.to_owned(),
+        narzędzie: Some(broń.narzędzie(reguły)),
+        kość: broń.kość(reguły),
+        ile: broń.kości(reguły),
+    }));
+    sprawdź_pulę(pula)
+}
+
+pub fn próba<'a, I>(
+    reguły: &Reguły,
+    wzór: &Wzorzec,
+    fach: Fach,
+    narzędzia: &[Narzędzie],
+    pomocnicy: I,
+    modyfikator: isize,
+) -> Result<Wynik, Błąd>
+where
+    I: Iterator<Item = (&'a str, &'a Wzorzec)>,
+{
+    Ok(Wynik {
+        rzut: "próba".to_owned(),
+        grupy: pula_próby(reguły, wzór, fach, narzędzia, pomocnicy, modyfikator)?
+            .iter()
+            .map(Pula::rzuć)
+            .collect(),
+        osłonięte: 0,
+        osłony: 0,
+        przerzuty: Vec::new(),
+    })
+}
+
+pub fn bitwa<'a, I, J>(
+    reguły: &Reguły,
+    wzór: &Wzorzec,
+    bronie: I,
+    osłony: J,
+    modyfikator: isize,
+) -> Result<Wynik, Błąd>
+where
+    I: Iterator<Item = (&'a str, &'a Broń)>,
+    J: Iterator<Item = &'a Osłona>,
+{
+    let mut wynik = Wynik {
+        rzut: "bitwa".to_owned(),
+        grupy: pula_bitwy(reguły, wzór, bronie, modyfikator)?
+            .iter()
+            .map(Pula::rzuć)
+            .collect(),
+        osłonięte: 0,
+        osłony: osłony.map(|osłona| osłona.skazy(reguły)).sum(),
+        przerzuty: Vec::new(),
+    };
+    wynik.osłoń();
+    Ok(wynik)
+}
+
+/// czy sprzęt utracił trwałość.
+pub fn zanik(reguły: &Reguły, durability: usize, quality: Narzędzie) -> Result<bool, Błąd> {
+    let wykładnik = durability
+        .try_into()
+        .map_err(|_| Błąd::NiepoprawnaWartość("trwałość", durability.to_string()))?;
+    Ok(Bernoulli::new(quality.decay(reguły)?.powi(wykładnik))
+        .map_err(|why| Błąd::NiepoprawneReguły(why.to_string()))?
+        .sample(&mut rand::thread_rng()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn opcje() {
+        let krótka = |ch, params: &[&str]| {
+            Arg::Short(ch, params.iter().map(|&param| param.to_owned()).collect())
+        };
+        let długa = |słowo: &str, params: &[&str]| {
+            Arg::Long(
+                słowo.to_owned(),
+                params.iter().map(|&param| param.to_owned()).collect(),
+            )
+        };
+
+        // skróty sprzed przeniesienia opcji do modułu komendy nadal działają
+        assert!(matches!(Wzorzec::try_parse_podstawowy(&krótka('s', &[])), Some(Wzorzec::Szelma)));
+        assert!(matches!(Zasięg::try_parse(&krótka('z', &["z"])), Some(Zasięg::Miotająca)));
+        assert!(matches!(Zasięg::try_parse(&krótka('z', &["b"])), Some(Zasięg::Biała)));
+
+        // długie opcje wskazują własną wartość, z tolerancją literówek podaną przy opcji
+        assert!(matches!(Wzorzec::podstawowy("szelma"), Some(Wzorzec::Szelma)));
+        assert!(matches!(Wzorzec::podstawowy("chojraczk"), Some(Wzorzec::Chojrak)));
+        assert!(matches!(Fach::try_parse(&długa("szkolony", &[])), Some(Fach::Szkolony)));
+        assert!(Fach::try_parse(&długa("znakomita", &[])).is_none());
+        assert!(matches!(
+            Narzędzie::try_parse(&długa("znakomta", &[])),
+            Some(Narzędzie::Znakomite)
+        ));
+        assert!(matches!(
+            Zasięg::try_parse(&długa("zasięg", &["miotająca"])),
+            Some(Zasięg::Miotająca)
+        ));
+        assert!(matches!(Narzędzie::try_parse(&długa("kiep", &[])), Some(Narzędzie::Kiepskie)));
+        assert!(Narzędzie::try_parse(&długa("kie", &[])).is_none());
+        assert!(matches!(Zasięg::try_parse(&długa("zasieg", &["b"])), Some(Zasięg::Biała)));
+        assert!(Zasięg::try_parse(&długa("zas", &["b"])).is_none());
+        assert!(matches!(Wzorzec::try_parse_podstawowy(&krótka('w', &[])), Some(Wzorzec::Szelma)));
+    }
+
+    #[test]
+    fn przerzut() {
+        let skaza = Kość::try_from(vec!["X".to_owned()]).unwrap();
+        let grupa = |rodzaj, ścianki: &[&[Znak]]| Grupa {
+            rodzaj,
+            nazwa: String::new(),
+            kość: skaza.clone(),
+            ścianki: ścianki.iter().map(|ścianka| ścianka.to_vec()).collect(),
+        };
+        let mut wynik = Wynik {
+            rzut: "bitwa".to_owned(),
+            grupy: vec![
+                grupa(RodzajKości::Wzorzec, &[&[Znak::Słońce], &[]]),
+                grupa(RodzajKości::Broń, &[&[Znak::Księżyc]]),
+            ],
+            osłonięte: 0,
+            osłony: 2,
+            przerzuty: Vec::new(),
+        };
+
+        // kości numerowane są od jedynki, a błędny numer niczego nie przerzuca
+        assert!(matches!(wynik.przerzuć(&[0]), Err(Błąd::NiepoprawnaWartość("kość", _))));
+        assert!(matches!(wynik.przerzuć(&[2, 4]), Err(Błąd::NiepoprawnaWartość("kość", _))));
+        assert!(wynik.przerzuty.is_empty());
+        assert_eq!(wynik.grupy[0].ścianki[1], []);
+
+        let przerzut = wynik.przerzuć(&[2, 3]).unwrap();
+        assert_eq!(
+            przerzut.iter().map(|kość| (kość.numer, kość.rodzaj)).collect::<Vec<_>>(),
+            [(2, RodzajKości::Wzorzec), (3, RodzajKości::Broń)]
+        );
+        assert_eq!(przerzut[1].przed, [Znak::Księżyc]);
+        assert_eq!(przerzut[1].po, [Znak::Skaza]);
+        // osłony zatrzymują również skazy wyrzucone w przerzucie
+        assert_eq!(wynik.liczba(Znak::Skaza), 0);
+
+        wynik.przerzuć(&[1]).unwrap();
+        assert_eq!(wynik.przerzuty.len(), 2);
+        assert_eq!(wynik.przerzuty[1][0].przed, [Znak::Słońce]);
+        assert_eq!(wynik.osłonięte, 2);
+        assert_eq!(wynik.liczba(Znak::Skaza), 1);
+    }
+
+    #[test]
+    fn uzbrojenie() {
+        let reguły = Reguły::default();
+        let broń = |waga, cechy: &[Cecha]| Broń {
+            waga,
+            zasięg: Zasięg::Biała,
+            cechy: cechy.iter().copied().collect(),
+        };
+        let lekka = broń(Waga::Lekka, &[]);
+        let ciężka = broń(Waga::Ciężka, &[]);
+        let dwuręczna = broń(Waga::Średnia, &[Cecha::Dwuręczna]);
+
+        assert!(sprawdź_uzbrojenie(&reguły, [&lekka, &ciężka].into_iter()).is_ok());
+        assert!(sprawdź_uzbrojenie(&reguły, [&dwuręczna].into_iter()).is_ok());
+        // broń dwuręczna zajmuje obie ręce
+        assert!(matches!(
+            sprawdź_uzbrojenie(&reguły, [&dwuręczna, &lekka].into_iter()),
+            Err(Błąd::Uzbrojenie("ręce", 2))
+        ));
+        assert!(matches!(
+            sprawdź_uzbrojenie(&reguły, [&ciężka, &ciężka].into_iter()),
+            Err(Błąd::Uzbrojenie("ciężkie", 1))
+        ));
+    }
+
+    #[test]
+    fn pule() {
+        let reguły = Reguły::default();
+        let pomocnik = Wzorzec::Chojrak;
+        let pula = pula_próby(
+            &reguły,
+            &Wzorzec::Szelma,
+            Fach::Szkolony,
+            &[Narzędzie::Kiepskie, Narzędzie::Znakomite],
+            [("ala", &pomocnik), ("ola", &pomocnik)].into_iter(),
+            -2,
+        )
+        .unwrap();
+        assert_eq!(
+            pula.iter().map(|kości| (kości.rodzaj, kości.ile)).collect::<Vec<_>>(),
+            [
+                (RodzajKości::Wzorzec, 3),
+                (RodzajKości::Narzędzie, 1),
+                (RodzajKości::Narzędzie, 1),
+                (RodzajKości::Pomoc, 1),
+                (RodzajKości::Pomoc, 1),
+            ]
+        );
+        assert_eq!(pula[4].nazwa, "ola");
+
+        // kara nie odbiera więcej kości, niż ma wzorzec
+        let pula =
+            pula_próby(&reguły, &Wzorzec::Szelma, Fach::Zielony, &[], iter::empty(), -10).unwrap();
+        assert_eq!(pula[0].ile, 0);
+
+        // żaden modyfikator nie pozwala przekroczyć limitu kości w rzucie
+        assert!(matches!(
+            pula_próby(&reguły, &Wzorzec::Szelma, Fach::Zielony, &[], iter::empty(), isize::MAX),
+            Err(Błąd::NiepoprawnaWartość("kości", _))
+        ));
+        assert!(pula_bitwy(&reguły, &Wzorzec::Szelma, iter::empty(), 16).is_ok());
+        assert!(matches!(
+            pula_bitwy(&reguły, &Wzorzec::Szelma, iter::empty(), 17),
+            Err(Błąd::NiepoprawnaWartość("kości", _))
+        ));
     }
 }